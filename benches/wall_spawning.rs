@@ -0,0 +1,78 @@
+//! Compares the one-entity-per-wall spawner with merged wall chunks on a
+//! large maze: entity count after spawning and the time of a physics step.
+//!
+//! Run with `cargo bench --bench wall_spawning`.
+
+use std::time::{Duration, Instant};
+
+use bevy::{prelude::*, transform::TransformPlugin};
+use bevy_xpbd_3d::prelude::*;
+
+use maze::{
+    maze::generate,
+    world::{self, CurrentMaze, MazeLayout, MazeMaterials, WallSpawning},
+};
+
+const SIZE: usize = 200;
+const STEPS: u32 = 60;
+
+fn main() {
+    for spawning in [
+        WallSpawning::PerWall,
+        WallSpawning::Merged { chunk_size: 16 },
+    ] {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            PhysicsPlugins::default(),
+        ))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .insert_resource(PhysicsTimestep::FixedOnce(1.0 / 60.0))
+        .insert_resource(CurrentMaze(generate::recursive_backtracker(SIZE, SIZE, 1)))
+        .init_resource::<MazeLayout>()
+        .init_resource::<MazeMaterials>()
+        .insert_resource(spawning)
+        .add_systems(Startup, (world::spawn_maze, spawn_balls));
+
+        let started = Instant::now();
+        app.update();
+        let spawn_time = started.elapsed();
+        let entities = app.world.entities().len();
+
+        // Only the physics schedule is timed, not the rest of the frame
+        // around it. The first frame set up its time step.
+        let mut step_time = Duration::ZERO;
+        for _ in 0..STEPS {
+            let started = Instant::now();
+            app.world.run_schedule(PhysicsSchedule);
+            step_time += started.elapsed();
+        }
+
+        println!(
+            "{spawning:?}: {entities} entities, first frame {spawn_time:?}, \
+             {:?} per physics step",
+            step_time / STEPS
+        );
+    }
+}
+
+/// A ball in every tenth cell of every tenth row, so the broad phase has
+/// dynamic bodies to pair with the walls.
+fn spawn_balls(mut commands: Commands, maze: Res<CurrentMaze>, layout: Res<MazeLayout>) {
+    let maze = &maze.0;
+    for y in (0..maze.height()).step_by(10) {
+        for x in (0..maze.width()).step_by(10) {
+            commands.spawn((
+                RigidBody::Dynamic,
+                Collider::ball(0.45),
+                SpatialBundle::from_transform(Transform::from_translation(
+                    layout.cell_center(maze, maze.cell(x, y)) + Vec3::Y * 0.5,
+                )),
+            ));
+        }
+    }
+}
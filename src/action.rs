@@ -0,0 +1,406 @@
+//! Actions the player can take, and the keys and buttons bound to them.
+//!
+//! Gameplay systems read [`Input<Action>`], which [`read_actions`] fills
+//! from the bound keys and gamepad buttons every frame, rather than looking
+//! at devices themselves. The [`Bindings`] load from and save to
+//! [`BINDINGS_FILE`], a JSON object from action names to lists of key and
+//! button names:
+//!
+//! ```json
+//! {
+//!   "jump": ["Space", "Gamepad:South"],
+//!   "hint": ["H", "Slash"]
+//! }
+//! ```
+//!
+//! Keys are named like the variants of [`KeyCode`], and gamepad buttons
+//! like those of [`GamepadButtonType`] after `Gamepad:`. Actions the file
+//! leaves out keep their default bindings, and an empty list unbinds one.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed},
+};
+use json::JsonValue;
+
+/// Where the bindings are kept, relative to the working directory.
+pub const BINDINGS_FILE: &str = "controls.json";
+
+/// Something the player can do, whatever device it comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    /// Grab or release the mouse cursor.
+    ToggleCursor,
+    ToggleFps,
+    Hint,
+    Pause,
+    /// Show or hide the controls for rebinding them.
+    Controls,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::ToggleCursor,
+        Action::ToggleFps,
+        Action::Hint,
+        Action::Pause,
+        Action::Controls,
+    ];
+
+    /// The name of the action in the bindings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move-forward",
+            Action::MoveBackward => "move-backward",
+            Action::MoveLeft => "move-left",
+            Action::MoveRight => "move-right",
+            Action::Jump => "jump",
+            Action::ToggleCursor => "toggle-cursor",
+            Action::ToggleFps => "toggle-fps",
+            Action::Hint => "hint",
+            Action::Pause => "pause",
+            Action::Controls => "controls",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// How the action is shown to the player.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Roll forward",
+            Action::MoveBackward => "Roll backward",
+            Action::MoveLeft => "Roll left",
+            Action::MoveRight => "Roll right",
+            Action::Jump => "Jump",
+            Action::ToggleCursor => "Grab cursor",
+            Action::ToggleFps => "FPS counter",
+            Action::Hint => "Hint",
+            Action::Pause => "Pause",
+            Action::Controls => "Controls",
+        }
+    }
+}
+
+/// The keys and gamepad buttons bound to every action.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct Bindings {
+    keys: BTreeMap<Action, Vec<KeyCode>>,
+    buttons: BTreeMap<Action, Vec<GamepadButtonType>>,
+}
+
+/// What names a gamepad button in the bindings file.
+const BUTTON_PREFIX: &str = "Gamepad:";
+
+/// Why the bindings could not be read.
+#[derive(Debug)]
+pub enum BindingsError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not valid JSON.
+    Json(json::Error),
+    /// An action is unknown or its keys are not acceptable.
+    Invalid { field: String, reason: String },
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io(e) => write!(f, "could not read bindings: {e}"),
+            BindingsError::Json(e) => write!(f, "invalid JSON: {e}"),
+            BindingsError::Invalid { field, reason } => write!(f, "invalid `{field}`: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BindingsError::Io(e) => Some(e),
+            BindingsError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BindingsError {
+    fn from(e: io::Error) -> Self {
+        BindingsError::Io(e)
+    }
+}
+
+impl From<json::Error> for BindingsError {
+    fn from(e: json::Error) -> Self {
+        BindingsError::Json(e)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let keys = [
+            (Action::MoveForward, KeyCode::W),
+            (Action::MoveBackward, KeyCode::S),
+            (Action::MoveLeft, KeyCode::A),
+            (Action::MoveRight, KeyCode::D),
+            (Action::Jump, KeyCode::Space),
+            (Action::ToggleCursor, KeyCode::Escape),
+            (Action::ToggleFps, KeyCode::F12),
+            (Action::Hint, KeyCode::H),
+            (Action::Pause, KeyCode::P),
+            (Action::Controls, KeyCode::F1),
+        ];
+        let buttons = [
+            (Action::Jump, GamepadButtonType::South),
+            (Action::Pause, GamepadButtonType::Start),
+        ];
+        // Every action has an entry in both maps, if only an empty one.
+        Bindings {
+            keys: by_action(&keys),
+            buttons: by_action(&buttons),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn buttons(&self, action: Action) -> &[GamepadButtonType] {
+        self.buttons.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The keys and buttons bound to `action` by their names in the
+    /// bindings file.
+    pub fn names(&self, action: Action) -> Vec<String> {
+        let keys = self.keys(action).iter().map(|key| format!("{key:?}"));
+        let buttons = self
+            .buttons(action)
+            .iter()
+            .map(|button| format!("{BUTTON_PREFIX}{button:?}"));
+        keys.chain(buttons).collect()
+    }
+
+    /// Binds `key` to `action` alone, in place of its other keys. Other
+    /// actions lose `key`, so that it does not do two things at once.
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        for keys in self.keys.values_mut() {
+            keys.retain(|&k| k != key);
+        }
+        self.keys.insert(action, vec![key]);
+    }
+
+    /// Like [`rebind`](Self::rebind) for a gamepad button, which replaces
+    /// the other buttons of `action` but none of its keys.
+    pub fn rebind_button(&mut self, action: Action, button: GamepadButtonType) {
+        for buttons in self.buttons.values_mut() {
+            buttons.retain(|&b| b != button);
+        }
+        self.buttons.insert(action, vec![button]);
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut root = JsonValue::new_object();
+        for action in Action::ALL {
+            root[action.name()] = self.names(action).into();
+        }
+        json::stringify_pretty(root, 2)
+    }
+
+    /// Reads the form described in the [module docs](self).
+    pub fn from_json(source: &str) -> Result<Self, BindingsError> {
+        let root = json::parse(source)?;
+        if !root.is_object() {
+            return Err(invalid("", "expected an object"));
+        }
+        let mut bindings = Bindings::default();
+        for (name, list) in root.entries() {
+            let action = Action::from_name(name).ok_or_else(|| invalid(name, "unknown action"))?;
+            if !list.is_array() {
+                return Err(invalid(name, "expected an array of key names"));
+            }
+            let (mut keys, mut buttons) = (Vec::new(), Vec::new());
+            for item in list.members() {
+                let unknown = || invalid(name, format!("unknown key {item}"));
+                let item = item.as_str().ok_or_else(unknown)?;
+                match item.strip_prefix(BUTTON_PREFIX) {
+                    Some(button) => buttons.push(variant(button).ok_or_else(unknown)?),
+                    None => keys.push(variant(item).ok_or_else(unknown)?),
+                }
+            }
+            bindings.keys.insert(action, keys);
+            bindings.buttons.insert(action, buttons);
+        }
+        Ok(bindings)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BindingsError> {
+        Bindings::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// The bindings in `path`, or the default ones if there is no such
+    /// file or it cannot be read. Only the latter is logged.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match Bindings::load(path) {
+            Ok(bindings) => bindings,
+            Err(BindingsError::Io(e)) if e.kind() == io::ErrorKind::NotFound => default(),
+            Err(e) => {
+                warn!("{}: {e}, using the default controls", path.display());
+                default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+fn by_action<T: Copy>(bound: &[(Action, T)]) -> BTreeMap<Action, Vec<T>> {
+    Action::ALL
+        .into_iter()
+        .map(|action| {
+            let items = bound.iter().filter(|&&(a, _)| a == action);
+            (action, items.map(|&(_, item)| item).collect())
+        })
+        .collect()
+}
+
+/// The variant named `name` of an enum of unit variants like [`KeyCode`].
+fn variant<T: FromReflect + Typed>(name: &str) -> Option<T> {
+    // Building a variant that does not exist panics, so look it up first.
+    let TypeInfo::Enum(info) = T::type_info() else {
+        return None;
+    };
+    info.variant(name)?;
+    T::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+}
+
+fn invalid(field: impl Into<String>, reason: impl Into<String>) -> BindingsError {
+    BindingsError::Invalid {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+/// Presses and releases the actions as their keys and the buttons of any
+/// connected gamepad are, see the [module docs](self).
+pub fn read_actions(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    bindings: Res<Bindings>,
+    mut actions: ResMut<Input<Action>>,
+) {
+    actions.clear();
+    for action in Action::ALL {
+        let key = bindings.keys(action).iter().any(|&key| keys.pressed(key));
+        let button = || {
+            bindings.buttons(action).iter().any(|&button| {
+                gamepads
+                    .iter()
+                    .any(|gamepad| buttons.pressed(GamepadButton::new(gamepad, button)))
+            })
+        };
+        if key || button() {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip_through_json() {
+        let mut bindings = Bindings::default();
+        bindings.rebind(Action::Jump, KeyCode::Return);
+        bindings.rebind(Action::Hint, KeyCode::W);
+        bindings.rebind_button(Action::Hint, GamepadButtonType::South);
+        assert!(bindings.keys(Action::MoveForward).is_empty());
+        assert_eq!(bindings.keys(Action::Jump), [KeyCode::Return]);
+        assert!(bindings.buttons(Action::Jump).is_empty());
+        assert_eq!(bindings.names(Action::Hint), ["W", "Gamepad:South"]);
+        assert_eq!(bindings.keys(Action::Hint), [KeyCode::W]);
+        assert_eq!(Bindings::from_json(&bindings.to_json()).unwrap(), bindings);
+
+        let partial =
+            Bindings::from_json(r#"{ "pause": ["Back", "Gamepad:Select", "F10"] }"#).unwrap();
+        assert_eq!(partial.keys(Action::Pause), [KeyCode::Back, KeyCode::F10]);
+        assert_eq!(partial.buttons(Action::Pause), [GamepadButtonType::Select]);
+        assert_eq!(partial.keys(Action::Jump), [KeyCode::Space]);
+    }
+
+    #[test]
+    fn bad_bindings_are_rejected() {
+        let error = |source| Bindings::from_json(source).unwrap_err().to_string();
+        assert_eq!(error(r#"{ "fly": [] }"#), "invalid `fly`: unknown action");
+        assert_eq!(
+            error(r#"{ "jump": ["Spacebar"] }"#),
+            "invalid `jump`: unknown key Spacebar"
+        );
+        assert_eq!(
+            error(r#"{ "jump": ["Gamepad:Space"] }"#),
+            "invalid `jump`: unknown key Gamepad:Space"
+        );
+        assert_eq!(
+            error(r#"{ "jump": "Space" }"#),
+            "invalid `jump`: expected an array of key names"
+        );
+        assert!(matches!(
+            Bindings::from_json("[]"),
+            Err(BindingsError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn actions_follow_their_keys() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Input<Action>>()
+            .init_resource::<Bindings>()
+            .add_systems(Update, read_actions);
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Space);
+        app.update();
+        let actions = app.world.resource::<Input<Action>>();
+        assert!(actions.just_pressed(Action::Jump));
+        assert!(!actions.pressed(Action::Hint));
+
+        app.update();
+        assert!(app.world.resource::<Input<Action>>().pressed(Action::Jump));
+        assert!(!app
+            .world
+            .resource::<Input<Action>>()
+            .just_pressed(Action::Jump));
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .release(KeyCode::Space);
+        app.update();
+        assert!(app
+            .world
+            .resource::<Input<Action>>()
+            .just_released(Action::Jump));
+    }
+}
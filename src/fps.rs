@@ -6,6 +6,8 @@ use bevy::prelude::*;
 use bevy::diagnostic::DiagnosticsStore;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 
+use crate::action::Action;
+
 /// Marker to find the container entity so we can show/hide the FPS counter
 #[derive(Component)]
 pub struct FpsRoot;
@@ -120,12 +122,12 @@ pub fn fps_text_update_system(
     }
 }
 
-/// Toggle the FPS counter when pressing F12
+/// Toggle the FPS counter on [`Action::ToggleFps`], F12 by default
 pub fn fps_counter_showhide(
     mut q: Query<&mut Visibility, With<FpsRoot>>,
-    kbd: Res<Input<KeyCode>>,
+    actions: Res<Input<Action>>,
 ) {
-    if kbd.just_pressed(KeyCode::F12) {
+    if actions.just_pressed(Action::ToggleFps) {
         let mut vis = q.single_mut();
         *vis = match *vis {
             Visibility::Hidden => Visibility::Visible,
@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    action::Action,
+    hint::Breadcrumb,
+    output::ExampleDisplay,
+    world::{CurrentMaze, GoalSensor, MazeLayout},
+};
+
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Playing,
+    Paused,
+    Won,
+}
+
+/// Sent once when the ball reaches the goal.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct MazeWon {
+    /// Time spent playing, not counting pauses.
+    pub elapsed: Duration,
+    /// Number of times the ball moved from one cell into another.
+    pub moves: u32,
+    /// Number of hints used.
+    pub hints: u32,
+}
+
+/// Progress through the current level.
+#[derive(Resource, Default, Debug)]
+pub struct LevelProgress {
+    pub elapsed: Duration,
+    pub moves: u32,
+    pub hints: u32,
+    /// The cell the ball was last seen in.
+    pub cell: Option<usize>,
+}
+
+#[derive(Component)]
+pub struct ResultsText;
+
+/// Starts the level once the world has been spawned.
+pub fn finish_loading(
+    mut progress: ResMut<LevelProgress>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    *progress = LevelProgress::default();
+    next_state.set(GameState::Playing);
+}
+
+/// Puts the ball back on the start cell and clears the last run's leftovers
+/// after the maze has been replaced, then loads the level again.
+pub fn restart_level(
+    mut commands: Commands,
+    mut ball: Query<
+        (&mut Transform, &mut LinearVelocity, &mut AngularVelocity),
+        With<ExampleDisplay>,
+    >,
+    leftovers: Query<Entity, Or<(With<ResultsText>, With<Breadcrumb>)>>,
+    maze: Res<CurrentMaze>,
+    layout: Res<MazeLayout>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (mut transform, mut linear, mut angular) in &mut ball {
+        transform.translation = layout.cell_center(&maze.0, maze.0.start()) + Vec3::Y * 0.5;
+        *linear = LinearVelocity::ZERO;
+        *angular = AngularVelocity::ZERO;
+    }
+    for entity in &leftovers {
+        commands.entity(entity).despawn_recursive();
+    }
+    next_state.set(GameState::Loading);
+}
+
+pub fn track_progress(
+    mut progress: ResMut<LevelProgress>,
+    ball: Query<&Transform, With<ExampleDisplay>>,
+    maze: Res<CurrentMaze>,
+    layout: Res<MazeLayout>,
+    time: Res<Time>,
+) {
+    progress.elapsed += time.delta();
+    let cell = layout.cell_at(&maze.0, ball.single().translation);
+    if cell.is_some() && cell != progress.cell {
+        if progress.cell.is_some() {
+            progress.moves += 1;
+        }
+        progress.cell = cell;
+    }
+}
+
+pub fn detect_goal(
+    goal: Query<&CollidingEntities, With<GoalSensor>>,
+    ball: Query<Entity, With<ExampleDisplay>>,
+    progress: Res<LevelProgress>,
+    mut won: EventWriter<MazeWon>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let ball = ball.single();
+    if goal.iter().any(|colliding| colliding.contains(&ball)) {
+        won.send(MazeWon {
+            elapsed: progress.elapsed,
+            moves: progress.moves,
+            hints: progress.hints,
+        });
+        next_state.set(GameState::Won);
+    }
+}
+
+/// Toggle pause on [`Action::Pause`], P by default
+pub fn toggle_pause(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    actions: Res<Input<Action>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        match state.get() {
+            GameState::Playing => next_state.set(GameState::Paused),
+            GameState::Paused => next_state.set(GameState::Playing),
+            _ => {}
+        }
+    }
+}
+
+pub fn pause_physics(mut physics_loop: ResMut<PhysicsLoop>) {
+    physics_loop.pause();
+}
+
+pub fn resume_physics(mut physics_loop: ResMut<PhysicsLoop>) {
+    physics_loop.resume();
+}
+
+pub fn show_results(mut commands: Commands, mut won: EventReader<MazeWon>) {
+    for won in won.read() {
+        commands.spawn((
+            ResultsText,
+            TextBundle::from_section(
+                format!(
+                    "Goal reached!\nTime: {:.1}s\nMoves: {}\nHints: {}",
+                    won.elapsed.as_secs_f32(),
+                    won.moves,
+                    won.hints
+                ),
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(40.0),
+                top: Val::Percent(40.0),
+                ..default()
+            }),
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+//! Analog input from gamepads.
+//!
+//! [`read_gamepads`] turns the sticks of every connected gamepad into
+//! [`Sticks`]: the left stick rolls the ball as hard as it is tilted and the
+//! right one turns the camera. Buttons are bound to actions like keys, see
+//! [`Bindings`](crate::action::Bindings). Gamepads may come and go at any
+//! time; without any the sticks are simply at rest.
+
+use bevy::prelude::*;
+
+/// How the sticks are read.
+#[derive(Resource, Clone, Debug)]
+pub struct StickSettings {
+    /// How far a stick has to be tilted before it counts, from 0 to 1.
+    /// Tilts beyond it are stretched to cover the whole range again.
+    pub dead_zone: f32,
+    /// How fast the right stick turns the camera at full tilt, in radians
+    /// per second.
+    pub look_sensitivity: f32,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        StickSettings {
+            dead_zone: 0.15,
+            look_sensitivity: 2.5,
+        }
+    }
+}
+
+/// Where the sticks of all gamepads together point this frame, with x to
+/// the right and y forward, each within -1 to 1.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Sticks {
+    /// The left stick, how hard to roll which way.
+    pub roll: Vec2,
+    /// The right stick, how fast to turn the camera which way.
+    pub look: Vec2,
+}
+
+/// Reads the sticks of every connected gamepad into [`Sticks`], see the
+/// [module docs](self).
+pub fn read_gamepads(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<StickSettings>,
+    mut sticks: ResMut<Sticks>,
+) {
+    let stick = |gamepad, x, y| {
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        dead_zone(Vec2::new(axis(x), axis(y)), settings.dead_zone)
+    };
+    let mut next = Sticks::default();
+    for gamepad in gamepads.iter() {
+        next.roll += stick(
+            gamepad,
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+        );
+        next.look += stick(
+            gamepad,
+            GamepadAxisType::RightStickX,
+            GamepadAxisType::RightStickY,
+        );
+    }
+    next.roll = next.roll.clamp(Vec2::NEG_ONE, Vec2::ONE);
+    next.look = next.look.clamp(Vec2::NEG_ONE, Vec2::ONE);
+    sticks.set_if_neq(next);
+}
+
+/// `tilt` with everything within `zone` of the middle cut off and the rest
+/// stretched back to a length of up to 1.
+fn dead_zone(tilt: Vec2, zone: f32) -> Vec2 {
+    let length = tilt.length();
+    if length <= zone {
+        return Vec2::ZERO;
+    }
+    let stretched = ((length - zone) / (1.0 - zone)).min(1.0);
+    tilt * (stretched / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+        InputPlugin,
+    };
+
+    use super::*;
+    use crate::action::{read_actions, Action, Bindings};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin)
+            .init_resource::<StickSettings>()
+            .init_resource::<Sticks>()
+            .init_resource::<Bindings>()
+            .init_resource::<Input<Action>>()
+            .add_systems(Update, (read_gamepads, read_actions));
+        app
+    }
+
+    fn connect(app: &mut App, gamepad: Gamepad, connected: bool) {
+        let connection = if connected {
+            GamepadConnection::Connected(GamepadInfo {
+                name: "test pad".into(),
+            })
+        } else {
+            GamepadConnection::Disconnected
+        };
+        app.world
+            .send_event(GamepadConnectionEvent::new(gamepad, connection));
+        app.update();
+    }
+
+    fn tilt(app: &mut App, gamepad: Gamepad, axis_type: GamepadAxisType, value: f32) {
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(GamepadAxis::new(gamepad, axis_type), value);
+    }
+
+    #[test]
+    fn sticks_follow_the_gamepads() {
+        let mut app = app();
+        app.update();
+        assert_eq!(*app.world.resource::<Sticks>(), Sticks::default());
+
+        let pad = Gamepad::new(0);
+        connect(&mut app, pad, true);
+        tilt(&mut app, pad, GamepadAxisType::LeftStickY, 1.0);
+        tilt(&mut app, pad, GamepadAxisType::RightStickX, -0.1);
+        app.update();
+        let sticks = *app.world.resource::<Sticks>();
+        assert_eq!(sticks.roll, Vec2::Y);
+        // Within the dead zone.
+        assert_eq!(sticks.look, Vec2::ZERO);
+
+        // Half way between the dead zone and full tilt rolls half as hard.
+        tilt(&mut app, pad, GamepadAxisType::LeftStickY, -0.575);
+        app.update();
+        let roll = app.world.resource::<Sticks>().roll;
+        assert!((roll - Vec2::new(0.0, -0.5)).length() < 1e-5, "{roll}");
+
+        // A second gamepad adds to the first, up to full tilt.
+        let other = Gamepad::new(3);
+        connect(&mut app, other, true);
+        tilt(&mut app, other, GamepadAxisType::LeftStickY, -1.0);
+        tilt(&mut app, other, GamepadAxisType::RightStickX, 1.0);
+        app.update();
+        let sticks = *app.world.resource::<Sticks>();
+        assert_eq!(sticks.roll, Vec2::NEG_Y);
+        assert_eq!(sticks.look, Vec2::X);
+
+        // The south button jumps on either gamepad.
+        app.world
+            .resource_mut::<Input<GamepadButton>>()
+            .press(GamepadButton::new(other, GamepadButtonType::South));
+        app.update();
+        assert!(app
+            .world
+            .resource::<Input<Action>>()
+            .just_pressed(Action::Jump));
+
+        // Unplugging them leaves the sticks at rest.
+        connect(&mut app, other, false);
+        connect(&mut app, pad, false);
+        connect(&mut app, pad, false);
+        assert_eq!(*app.world.resource::<Sticks>(), Sticks::default());
+        assert!(!app.world.resource::<Input<Action>>().pressed(Action::Jump));
+    }
+}
@@ -0,0 +1,133 @@
+//! Ground detection for round bodies such as the ball.
+//!
+//! Every frame [`detect_ground`] casts a sphere a little smaller than the
+//! body straight down from its center and reports what it hits in the
+//! body's [`Grounded`] component. Floors on any storey, ramps and the tops
+//! of walls all count alike, as long as they are not too steep.
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+/// How far below a body resting on the ground the cast still finds it, so
+/// that small bounces do not count as leaving it.
+const REACH: f32 = 0.05;
+
+/// Whether a body stands on the ground, for other systems to read. Kept up
+/// to date by [`detect_ground`].
+#[derive(Component, Clone, Debug)]
+pub struct Grounded {
+    /// Radius of the body, whose bottom is taken to be round.
+    pub radius: f32,
+    /// Steepest slope that still counts as ground, in radians.
+    pub max_slope: f32,
+    /// Normal of the ground under the body, `None` while it is airborne.
+    pub normal: Option<Vec3>,
+    /// Seconds since the body was last on the ground.
+    pub airborne: f32,
+}
+
+impl Grounded {
+    /// A body of `radius` that has not touched the ground yet, standing on
+    /// slopes of up to 50°.
+    pub fn new(radius: f32) -> Self {
+        Grounded {
+            radius,
+            max_slope: 50f32.to_radians(),
+            normal: None,
+            airborne: f32::INFINITY,
+        }
+    }
+
+    pub fn on_ground(&self) -> bool {
+        self.normal.is_some()
+    }
+
+    /// Whether the body is on the ground or left it at most `grace` seconds
+    /// ago, which lets it still jump right after rolling off an edge.
+    pub fn within(&self, grace: f32) -> bool {
+        self.airborne <= grace
+    }
+
+    /// Takes in the surface normal under the body after `elapsed` seconds,
+    /// if the cast hit anything.
+    fn update(&mut self, hit: Option<Vec3>, elapsed: f32) {
+        self.normal = hit.filter(|normal| normal.y >= self.max_slope.cos());
+        self.airborne = match self.normal {
+            Some(_) => 0.0,
+            None => self.airborne + elapsed,
+        };
+    }
+}
+
+/// Updates every [`Grounded`] body, see the [module docs](self).
+pub fn detect_ground(
+    mut bodies: Query<(Entity, &Transform, &mut Grounded)>,
+    rotations: Query<&Rotation>,
+    spatial: SpatialQuery,
+    time: Res<Time>,
+) {
+    for (entity, transform, mut grounded) in &mut bodies {
+        // The smaller sphere only reaches the ground below the body, not
+        // the walls it rolls along.
+        let probe = 0.9 * grounded.radius;
+        let hit = spatial.cast_shape(
+            &Collider::ball(probe),
+            transform.translation,
+            Quat::IDENTITY,
+            Vec3::NEG_Y,
+            grounded.radius - probe + REACH,
+            true,
+            SpatialQueryFilter::default().without_entities([entity]),
+        );
+        let normal = hit.map(|hit| world_normal(hit.normal1, rotations.get(hit.entity).ok()));
+        grounded.update(normal, time.delta_seconds());
+    }
+}
+
+/// Turns a `normal` from the local space of the collider hit, as shape
+/// casts report it, into world space.
+fn world_normal(normal: Vec3, rotation: Option<&Rotation>) -> Vec3 {
+    match rotation {
+        Some(rotation) => rotation.0 * normal,
+        None => normal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steep_slopes_are_not_ground() {
+        let mut grounded = Grounded::new(0.5);
+        assert!(!grounded.on_ground() && !grounded.within(1.0));
+        grounded.update(Some(Vec3::Y), 0.1);
+        assert!(grounded.on_ground() && grounded.within(0.0));
+
+        // A ramp rising 30° still carries the body, a wall does not.
+        let ramp = Quat::from_rotation_x(30f32.to_radians()) * Vec3::Y;
+        grounded.update(Some(ramp), 0.1);
+        assert_eq!(grounded.normal, Some(ramp));
+        grounded.update(Some(Vec3::X), 0.1);
+        assert!(!grounded.on_ground());
+        grounded.update(None, 0.1);
+        assert!(grounded.within(0.2) && !grounded.within(0.15));
+    }
+
+    #[test]
+    fn normals_follow_tilted_colliders() {
+        let mut grounded = Grounded::new(0.5);
+        // The top of a box tipped 30° is still ground, tipped on its side
+        // it is a wall, though its local normal points up either way.
+        let ramp = Rotation(Quat::from_rotation_z(30f32.to_radians()));
+        grounded.update(Some(world_normal(Vec3::Y, Some(&ramp))), 0.1);
+        let normal = grounded.normal.unwrap();
+        assert!((normal - ramp.0 * Vec3::Y).length() < 1e-6, "{normal}");
+        assert!(normal.x < 0.0);
+
+        let side = Rotation(Quat::from_rotation_z(90f32.to_radians()));
+        grounded.update(Some(world_normal(Vec3::Y, Some(&side))), 0.1);
+        assert!(!grounded.on_ground());
+        assert_eq!(world_normal(Vec3::Y, None), Vec3::Y);
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    game::LevelProgress,
+    maze::solve::{solve, Search},
+    output::ExampleDisplay,
+    world::{CurrentMaze, MazeLayout},
+};
+
+/// Sent when the player asks for a hint.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct HintRequested;
+
+#[derive(Resource, Clone, Debug)]
+pub struct HintSettings {
+    /// How many cells of the path to the goal a hint reveals.
+    pub length: usize,
+    /// How long the breadcrumbs take to fade out.
+    pub fade: Duration,
+    /// Minimum time between two hints.
+    pub cooldown: Duration,
+}
+
+impl Default for HintSettings {
+    fn default() -> Self {
+        HintSettings {
+            length: 5,
+            fade: Duration::from_secs(4),
+            cooldown: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Time left until the next hint may be shown.
+#[derive(Resource, Default, Debug)]
+pub struct HintCooldown(pub Duration);
+
+/// A glowing floor marker on the path to the goal.
+#[derive(Component)]
+pub struct Breadcrumb {
+    pub timer: Timer,
+}
+
+/// Shows the next few cells of the shortest path from the ball to the goal
+/// as breadcrumbs. Each hint counts against the player in
+/// [`LevelProgress::hints`].
+#[allow(clippy::too_many_arguments)]
+pub fn show_hint(
+    mut commands: Commands,
+    mut requests: EventReader<HintRequested>,
+    mut cooldown: ResMut<HintCooldown>,
+    mut progress: ResMut<LevelProgress>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ball: Query<&Transform, With<ExampleDisplay>>,
+    maze: Res<CurrentMaze>,
+    layout: Res<MazeLayout>,
+    settings: Res<HintSettings>,
+) {
+    if requests.read().count() == 0 || !cooldown.0.is_zero() {
+        return;
+    }
+    let maze = &maze.0;
+    let Some(cell) = layout.cell_at(maze, ball.single().translation) else {
+        return;
+    };
+    let Some(path) = solve(maze, cell, maze.goal(), Search::AStar) else {
+        return;
+    };
+
+    cooldown.0 = settings.cooldown;
+    progress.hints += 1;
+    let mesh = meshes.add(Mesh::from(shape::UVSphere {
+        radius: layout.cell_size * 0.08,
+        sectors: 16,
+        stacks: 8,
+    }));
+    for (i, &cell) in path.iter().skip(1).take(settings.length).enumerate() {
+        // Later breadcrumbs fade out sooner, so the trail shrinks towards
+        // the ball.
+        let fade = settings
+            .fade
+            .mul_f32(1.0 - i as f32 / (settings.length as f32 + 1.0));
+        commands.spawn((
+            Breadcrumb {
+                timer: Timer::new(fade, TimerMode::Once),
+            },
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::CYAN,
+                    emissive: Color::CYAN * 8.0,
+                    ..default()
+                }),
+                transform: Transform::from_translation(
+                    layout.cell_center(maze, cell) + Vec3::Y * 0.1,
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Fades breadcrumbs out and counts down the hint cooldown.
+pub fn fade_breadcrumbs(
+    mut commands: Commands,
+    mut breadcrumbs: Query<(Entity, &mut Breadcrumb, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cooldown: ResMut<HintCooldown>,
+    time: Res<Time>,
+) {
+    cooldown.0 = cooldown.0.saturating_sub(time.delta());
+    for (entity, mut breadcrumb, material) in &mut breadcrumbs {
+        breadcrumb.timer.tick(time.delta());
+        if breadcrumb.timer.finished() {
+            materials.remove(material);
+            commands.entity(entity).despawn();
+        } else if let Some(material) = materials.get_mut(material) {
+            material.emissive = Color::CYAN * 8.0 * breadcrumb.timer.percent_left();
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Infinite mode, in which the maze keeps growing southwards ahead of the
+//! ball.
+//!
+//! Whenever the ball gets within [`InfiniteMaze::ahead`] rows of the south
+//! edge, new rows are carved onto it with [`generate::extend`], which always
+//! leaves a way on from the old frontier. Only the new rows are spawned; the
+//! rows already there keep their geometry, except for the wall along the
+//! south edge, which moves with it. The score is the furthest row the ball
+//! has reached.
+
+use std::ops::Range;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::components::RigidBody;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    maze::{generate, wall_runs_in, Maze},
+    output::ExampleDisplay,
+    world::{boxes_collider, boxes_mesh, CurrentMaze, MazeLayout, MazeMaterials},
+};
+
+/// Turns on infinite mode while present; [`CurrentMaze`] is then replaced
+/// and grown by [`grow_maze`] rather than spawned as a whole.
+#[derive(Resource, Debug)]
+pub struct InfiniteMaze {
+    /// The generator carving the new rows, by its name for
+    /// [`generate::by_name`].
+    pub generator: String,
+    /// Cells across the maze.
+    pub width: usize,
+    /// Rows carved at a time.
+    pub rows: usize,
+    /// Rows kept carved ahead of the ball.
+    pub ahead: usize,
+    /// The furthest row the ball has reached, which is the score.
+    pub distance: usize,
+    /// Rows spawned so far, none before the maze has been replaced.
+    spawned: usize,
+    rng: StdRng,
+}
+
+impl Default for InfiniteMaze {
+    /// A maze 8 cells across from a random seed, which is logged so the
+    /// maze can be reported and reproduced.
+    fn default() -> Self {
+        let seed = rand::random();
+        info!("infinite maze seed: {seed}");
+        InfiniteMaze {
+            generator: "backtracker".into(),
+            width: 8,
+            rows: 8,
+            ahead: 12,
+            distance: 0,
+            spawned: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// The spawned geometry of some rows of the infinite maze.
+#[derive(Component)]
+pub struct InfiniteRows(pub Range<usize>);
+
+/// The wall along the south edge of the infinite maze, respawned whenever
+/// the maze grows.
+#[derive(Component)]
+pub struct Frontier;
+
+#[derive(Component)]
+pub struct DistanceText;
+
+/// Starts the infinite maze, then carves and spawns new rows as the ball
+/// gets close to the south edge, see the [module docs](self).
+#[allow(clippy::too_many_arguments)]
+pub fn grow_maze(
+    mut commands: Commands,
+    ball: Query<&Transform, With<ExampleDisplay>>,
+    frontier: Query<Entity, With<Frontier>>,
+    mut infinite: ResMut<InfiniteMaze>,
+    mut maze: ResMut<CurrentMaze>,
+    layout: Res<MazeLayout>,
+    materials: Res<MazeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let infinite = &mut *infinite;
+    let Some(generator) = generate::by_name(&infinite.generator) else {
+        warn!("unknown infinite maze generator {:?}", infinite.generator);
+        return;
+    };
+    if infinite.spawned == 0 {
+        // Replacing the maze puts the ball on its start.
+        let mut first = Maze::new(infinite.width, infinite.rows);
+        generator.carve(&mut first, &mut infinite.rng);
+        maze.0 = first;
+        infinite.distance = 0;
+    } else {
+        let Ok(ball) = ball.get_single() else {
+            return;
+        };
+        let row = (ball.translation.z / layout.cell_size).max(0.0) as usize;
+        infinite.distance = infinite.distance.max(row);
+        // Growing the maze is no reason to restart on it.
+        let maze = &mut maze.bypass_change_detection().0;
+        while maze.height() < row + infinite.ahead {
+            generate::extend(maze, generator.as_ref(), infinite.rows, &mut infinite.rng);
+        }
+        if maze.height() == infinite.spawned {
+            return;
+        }
+        maze.set_goal(maze.cell_count() - 1);
+    }
+
+    let maze = &maze.0;
+    let rows = infinite.spawned..maze.height();
+    infinite.spawned = maze.height();
+    for entity in &frontier {
+        commands.entity(entity).despawn();
+    }
+    let (floor, walls, south) = row_boxes(&layout, maze, rows.clone());
+    let mut solid = walls.clone();
+    solid.push(floor);
+    commands
+        .spawn((
+            InfiniteRows(rows),
+            RigidBody::Static,
+            boxes_collider(&solid),
+            SpatialBundle::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(boxes_mesh(&[floor])),
+                material: materials.tile.clone(),
+                ..default()
+            });
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(boxes_mesh(&walls)),
+                material: materials.wall.clone(),
+                ..default()
+            });
+        });
+    commands.spawn((
+        Frontier,
+        RigidBody::Static,
+        boxes_collider(&south),
+        PbrBundle {
+            mesh: meshes.add(boxes_mesh(&south)),
+            material: materials.wall.clone(),
+            ..default()
+        },
+    ));
+}
+
+/// Shows the distance reached, the score of infinite mode.
+pub fn show_distance(
+    mut commands: Commands,
+    infinite: Res<InfiniteMaze>,
+    mut text: Query<&mut Text, With<DistanceText>>,
+) {
+    let value = format!("Distance: {}", infinite.distance);
+    let Ok(mut text) = text.get_single_mut() else {
+        commands.spawn((
+            DistanceText,
+            TextBundle::from_section(
+                value,
+                TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(1.0),
+                top: Val::Percent(1.0),
+                ..default()
+            }),
+        ));
+        return;
+    };
+    if infinite.is_changed() {
+        text.sections[0].value = value;
+    }
+}
+
+/// The floor slab, the wall runs and the south border of `rows` of `maze`
+/// as sizes and centers. The walls include the north side of the first
+/// row, which is the old south border with the way on cut into it.
+fn row_boxes(
+    layout: &MazeLayout,
+    maze: &Maze,
+    rows: Range<usize>,
+) -> ((Vec3, Vec3), Vec<(Vec3, Vec3)>, Vec<(Vec3, Vec3)>) {
+    let depth = rows.len() as f32 * layout.cell_size;
+    let width = layout.extent(maze).x;
+    let floor = (
+        Vec3::new(width, layout.floor_thickness, depth),
+        Vec3::new(
+            width / 2.0,
+            layout.floor_y - layout.floor_thickness / 2.0,
+            rows.start as f32 * layout.cell_size + depth / 2.0,
+        ),
+    );
+    let (south, walls) = wall_runs_in(maze, rows, maze.height())
+        .into_iter()
+        .partition::<Vec<_>, _>(|run| run.horizontal && run.line == maze.height());
+    let boxes = |runs: Vec<_>| runs.iter().map(|run| layout.wall_run(run)).collect();
+    (floor, boxes(walls), boxes(south))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generate::{MazeGenerator, Prim};
+
+    #[test]
+    fn new_rows_open_onto_the_old_frontier() {
+        let layout = MazeLayout::default();
+        let mut maze = Prim.generate(6, 4, 9);
+        let exit = generate::extend(&mut maze, &Prim, 5, &mut StdRng::seed_from_u64(9));
+        let (floor, walls, south) = row_boxes(&layout, &maze, 4..9);
+        assert_eq!(floor.1.z - floor.0.z / 2.0, 4.0 * layout.cell_size);
+        assert_eq!(floor.1.z + floor.0.z / 2.0, 9.0 * layout.cell_size);
+
+        // The south border is one wall across the maze.
+        assert_eq!(south.len(), 1);
+        assert_eq!(south[0].1.z, 9.0 * layout.cell_size);
+        assert!(south[0].0.x > 6.0 * layout.cell_size);
+
+        // The old frontier has a gap over the exit and nothing else of the
+        // old rows is spawned again.
+        let exit_x = (maze.coords(exit).0 as f32 + 0.5) * layout.cell_size;
+        let frontier = walls
+            .iter()
+            .filter(|(size, center)| size.x > size.z && center.z == 4.0 * layout.cell_size)
+            .collect::<Vec<_>>();
+        assert!(!frontier.is_empty());
+        for (size, center) in frontier {
+            assert!((exit_x - center.x).abs() >= size.x / 2.0);
+        }
+        for (size, center) in &walls {
+            assert!(center.z - size.z / 2.0 >= 4.0 * layout.cell_size - layout.wall_thickness);
+        }
+    }
+}
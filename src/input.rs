@@ -20,18 +20,46 @@
 use bevy::{input::mouse::MouseMotion, prelude::*, window::CursorGrabMode};
 use bevy_xpbd_3d::components::{AngularVelocity, LinearVelocity};
 
-use super::output::ExampleDisplay;
+use super::{
+    action::Action,
+    gamepad::{StickSettings, Sticks},
+    ground::Grounded,
+    hint::HintRequested,
+    output::ExampleDisplay,
+    world::{CurrentMaze, MazeLayout},
+};
+
+/// How fast the ball spins when rolling at full force, in radians per
+/// second.
+const ROLL_SPEED: f32 = 5.0;
+/// How long after rolling off an edge the ball can still jump, in seconds.
+const COYOTE_TIME: f32 = 0.12;
+/// How long a jump pressed in the air is kept for landing, in seconds.
+const JUMP_BUFFER: f32 = 0.15;
+
+/// Seconds since the jump key was pressed, until the press is used up or
+/// too old, and since the last jump.
+#[derive(Default)]
+pub struct JumpTimers {
+    pressed: Option<f32>,
+    jumped: Option<f32>,
+}
 
 #[allow(clippy::too_many_arguments)]
 pub fn deal_input(
     mut camera: Query<(&mut Transform,), With<Camera3d>>,
     mut r_ball: Query<
-        (&mut AngularVelocity, &mut LinearVelocity, &mut Transform),
+        (&mut AngularVelocity, &mut LinearVelocity, &mut Transform, &Grounded),
         (With<ExampleDisplay>, Without<Camera3d>),
     >,
     mut windows: Query<&mut Window>,
-    input: Res<Input<KeyCode>>,
+    actions: Res<Input<Action>>,
     mouse_events: Res<'_, Events<MouseMotion>>,
+    sticks: Res<Sticks>,
+    stick_settings: Res<StickSettings>,
+    mut hints: EventWriter<HintRequested>,
+    time: Res<Time>,
+    mut jump: Local<JumpTimers>,
 ) {
     let mut ball = r_ball.single_mut();
     let (mut camera_transform,) = camera.single_mut();
@@ -45,7 +73,7 @@ pub fn deal_input(
     camera_transform.translation += c_z * 2.0 + Vec3::Y;
 
     let mut window = windows.single_mut();
-    if input.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::ToggleCursor) {
         if window.cursor.visible {
             window.cursor.visible = false;
             window.cursor.grab_mode = CursorGrabMode::Locked;
@@ -66,20 +94,83 @@ pub fn deal_input(
             c_x = c_x.normalize();
         }
     }
+    let dt = time.delta_seconds();
+    // The right stick turns the camera like the mouse, up for up.
+    if sticks.look != Vec2::ZERO {
+        let axis = sticks.look.y * c_x - sticks.look.x * Vec3::Y;
+        camera_transform.rotate_axis(
+            axis.normalize(),
+            sticks.look.length() * stick_settings.look_sensitivity * dt,
+        );
+
+        c_x = camera_transform.transform_point(Vec3::X)
+            - camera_transform.transform_point(Vec3::ZERO);
+        c_x.y = 0.0;
+        c_x = c_x.normalize();
+    }
     c_z = c_x.cross(Vec3::Y);
 
-    ball.0.0 = Vec3::ZERO;
-    if input.pressed(KeyCode::W) {
-        ball.0.0 -= c_x * 5.0;
-    } else if input.pressed(KeyCode::S) {
-        ball.0.0 += c_x * 5.0;
+    // Keys roll at full force, the left stick as far as it is tilted.
+    let mut roll = sticks.roll;
+    if actions.pressed(Action::MoveForward) {
+        roll.y += 1.0;
+    } else if actions.pressed(Action::MoveBackward) {
+        roll.y -= 1.0;
     }
-    if input.pressed(KeyCode::A) {
-        ball.0.0 += c_z * 5.0;
-    } else if input.pressed(KeyCode::D) {
-        ball.0.0 -= c_z * 5.0;
+    if actions.pressed(Action::MoveLeft) {
+        roll.x -= 1.0;
+    } else if actions.pressed(Action::MoveRight) {
+        roll.x += 1.0;
     }
-    if (ball.2.transform_point(Vec3::ZERO).y + 0.5).abs() < 0.1 && input.just_pressed(KeyCode::Space) {
+    let roll = roll.clamp(Vec2::NEG_ONE, Vec2::ONE);
+    ball.0.0 = (-c_x * roll.y - c_z * roll.x) * ROLL_SPEED;
+    jump.pressed = if actions.just_pressed(Action::Jump) {
+        Some(0.0)
+    } else {
+        jump.pressed.map(|t| t + dt).filter(|&t| t <= JUMP_BUFFER)
+    };
+    jump.jumped = jump.jumped.map(|t| t + dt);
+    // The ground stays in reach for a moment after taking off, which must
+    // not count as being on it again.
+    let landed = !matches!(jump.jumped, Some(t) if t <= COYOTE_TIME);
+    if jump.pressed.is_some() && landed && ball.3.within(COYOTE_TIME) {
         ball.1.y += 5.0;
+        jump.pressed = None;
+        jump.jumped = Some(0.0);
+    }
+    if actions.just_pressed(Action::Hint) {
+        hints.send(HintRequested);
+    }
+}
+
+/// Smooths out the camera height that [`deal_input`] snaps to the ball, so
+/// rolling up a ramp or dropping to another floor does not jolt the view.
+/// In mazes with several floors the camera also stays under the floor
+/// above the ball.
+pub fn ease_camera_height(
+    mut camera: Query<&mut Transform, With<Camera3d>>,
+    ball: Query<&Transform, (With<ExampleDisplay>, Without<Camera3d>)>,
+    maze: Res<CurrentMaze>,
+    layout: Res<MazeLayout>,
+    time: Res<Time>,
+    mut height: Local<Option<f32>>,
+) {
+    let mut camera = camera.single_mut();
+    // A new maze starts over at the start cell.
+    if maze.is_changed() {
+        *height = None;
+    }
+    let maze = &maze.0;
+    let mut target = camera.translation.y;
+    if maze.floors() > 1 {
+        let floor = layout.floor_at(maze, ball.single().translation.y);
+        let ceiling = layout.floor_y + floor as f32 * layout.floor_spacing() + layout.wall_height;
+        target = target.min(ceiling - 0.15);
     }
+    let eased = match *height {
+        Some(h) => h + (target - h) * (1.0 - (-8.0 * time.delta_seconds()).exp()),
+        None => target,
+    };
+    camera.translation.y = eased;
+    *height = Some(eased);
 }
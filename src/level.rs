@@ -0,0 +1,797 @@
+//! Level files.
+//!
+//! A level is a maze plus everything a designer adds on top of it, stored
+//! as versioned JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "name": "Tutorial",
+//!   "author": "maze team",
+//!   "par_time": 30,
+//!   "theme": "stone",
+//!   "width": 2,
+//!   "height": 1,
+//!   "walls": [13, 7],
+//!   "start": [0, 0],
+//!   "goal": [1, 0],
+//!   "items": [{ "kind": "coin", "cell": [1, 0] }]
+//! }
+//! ```
+//!
+//! `walls` lists one bitmask per cell, row by row, with a bit for every
+//! walled side: 1 north, 2 east, 4 south, 8 west. Hex mazes add
+//! `"topology": "hex"` and use 1 north, 4 south, 16 north-east,
+//! 32 south-east, 64 south-west and 128 north-west. Polar mazes add
+//! `"topology": "polar"`, list their cells ring by ring from the center
+//! and use the bits of the sides described at [`Topology::Polar`].
+//!
+//! Square mazes may stack several floors with `"floors": 3`. Their walls
+//! list one floor after another from the bottom, with 256 for up and 512
+//! for down, and every passage up has to be laid out as a ramp, see
+//! [`floors`](crate::maze::floors). Cells then name their floor as well,
+//! as in `[x, y, floor]`.
+//!
+//! Weave mazes list their crossings with the way the tunnel under each
+//! runs, as in `"crossings": [{ "cell": [2, 3], "tunnel": "north-south" }]`,
+//! and the walls of the tunnels follow those of the other cells in the
+//! same order. Both ends of every bridge need a ramp, see
+//! [`weave`](crate::maze::weave).
+//!
+//! Masked square and hex mazes list the cells left out of the maze, as in
+//! `"holes": [[0, 0], [4, 0]]`. Holes have no sides, so their walls are 0,
+//! and nothing may be placed on them; see [`mask`](crate::maze::mask).
+//!
+//! Files with the `.maze` extension load as [`MazeLevel`] assets through
+//! [`MazeLevelLoader`]; the level passed on the command line is watched and
+//! the world rebuilt whenever it changes on disk.
+
+use std::{fmt, io};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use json::{object, JsonValue};
+
+use crate::{
+    maze::{mask::Mask, Direction, Maze, Topology},
+    world::CurrentMaze,
+};
+
+/// The newest format version; the only one [`MazeLevel::from_json`] reads.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Asset, TypePath, Clone, Debug, PartialEq)]
+pub struct MazeLevel {
+    pub maze: Maze,
+    pub items: Vec<Item>,
+    pub theme: String,
+    pub meta: LevelMeta,
+}
+
+/// Something placed in a cell, such as a pickup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Item {
+    pub kind: String,
+    pub cell: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LevelMeta {
+    pub name: String,
+    pub author: String,
+    /// Target completion time in seconds.
+    pub par_time: Option<f64>,
+}
+
+/// Why a level file could not be read.
+#[derive(Debug)]
+pub enum LevelError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not valid JSON.
+    Json(json::Error),
+    /// A required field is absent.
+    Missing(String),
+    /// A field is present but its value is not acceptable.
+    Invalid { field: String, reason: String },
+    /// The file was written by a newer or unknown format version.
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "could not read level: {e}"),
+            LevelError::Json(e) => write!(f, "invalid JSON: {e}"),
+            LevelError::Missing(field) => write!(f, "missing field `{field}`"),
+            LevelError::Invalid { field, reason } => write!(f, "invalid `{field}`: {reason}"),
+            LevelError::UnsupportedVersion(v) => write!(f, "unsupported level version {v}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelError::Io(e) => Some(e),
+            LevelError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(e: io::Error) -> Self {
+        LevelError::Io(e)
+    }
+}
+
+impl From<json::Error> for LevelError {
+    fn from(e: json::Error) -> Self {
+        LevelError::Json(e)
+    }
+}
+
+impl MazeLevel {
+    /// A level with no items or metadata around `maze`.
+    pub fn new(maze: Maze) -> Self {
+        MazeLevel {
+            maze,
+            items: Vec::new(),
+            theme: String::new(),
+            meta: LevelMeta::default(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let maze = &self.maze;
+        let cell = |cell: usize| {
+            let (x, y) = maze.coords(cell);
+            match maze.floors() {
+                1 => JsonValue::from(vec![x, y]),
+                _ => JsonValue::from(vec![x, y, maze.floor(cell)]),
+            }
+        };
+        let mut root = object! {
+            version: FORMAT_VERSION,
+            name: self.meta.name.as_str(),
+            author: self.meta.author.as_str(),
+            theme: self.theme.as_str(),
+            width: maze.width(),
+            height: maze.height(),
+            walls: maze.cells().map(|c| maze.wall_mask(c)).collect::<Vec<_>>(),
+            start: cell(maze.start()),
+            goal: cell(maze.goal()),
+        };
+        if maze.topology() != Topology::Square {
+            root["topology"] = maze.topology().name().into();
+        }
+        if maze.floors() > 1 {
+            root["floors"] = maze.floors().into();
+        }
+        if maze.hole_count() > 0 {
+            root["holes"] = maze
+                .cells()
+                .filter(|&c| !maze.is_enabled(c))
+                .map(cell)
+                .collect::<Vec<_>>()
+                .into();
+        }
+        if maze.tunnel_count() > 0 {
+            root["crossings"] = (maze.cell_count() - maze.tunnel_count()..maze.cell_count())
+                .filter_map(|tunnel| maze.tunnel(tunnel))
+                .map(|(crossing, along)| {
+                    object! { cell: cell(crossing), tunnel: tunnel_name(along) }
+                })
+                .collect::<Vec<_>>()
+                .into();
+        }
+        if let Some(par_time) = self.meta.par_time {
+            root["par_time"] = par_time.into();
+        }
+        root["items"] = self
+            .items
+            .iter()
+            .map(|item| object! { kind: item.kind.as_str(), cell: cell(item.cell) })
+            .collect::<Vec<_>>()
+            .into();
+        json::stringify_pretty(root, 2)
+    }
+
+    pub fn from_json(source: &str) -> Result<Self, LevelError> {
+        let root = json::parse(source)?;
+        if !root.is_object() {
+            return Err(invalid("", "expected an object"));
+        }
+
+        let version = field(&root, "version")?
+            .as_u64()
+            .ok_or_else(|| invalid("version", "expected an integer"))?;
+        if version != u64::from(FORMAT_VERSION) {
+            return Err(LevelError::UnsupportedVersion(version));
+        }
+
+        let width = dimension(&root, "width")?;
+        let height = dimension(&root, "height")?;
+        let topology = match &root["topology"] {
+            JsonValue::Null => Topology::Square,
+            value => value
+                .as_str()
+                .and_then(Topology::from_name)
+                .ok_or_else(|| invalid("topology", "expected \"square\", \"hex\" or \"polar\""))?,
+        };
+        if topology == Topology::Polar && !(3..=8).contains(&width) {
+            return Err(invalid(
+                "width",
+                "polar mazes need 3 to 8 cells around the center",
+            ));
+        }
+        let floors = match &root["floors"] {
+            JsonValue::Null => 1,
+            _ => dimension(&root, "floors")?,
+        };
+        if floors > 1 && topology != Topology::Square {
+            return Err(invalid(
+                "floors",
+                "only square mazes can have several floors",
+            ));
+        }
+        let walls = field(&root, "walls")?;
+        if !walls.is_array() {
+            return Err(invalid("walls", "expected an array"));
+        }
+        // Check the size against the walls before allocating anything.
+        let tunnels = root["crossings"].len();
+        Maze::cell_count_for(topology, width, height, floors, walls.len())
+            .filter(|&cells| cells + tunnels == walls.len())
+            .ok_or_else(|| {
+                invalid(
+                    "width",
+                    format!(
+                        "the size does not match the {} cells in `walls`",
+                        walls.len()
+                    ),
+                )
+            })?;
+        let mut maze = match floors {
+            1 => Maze::with_topology(topology, width, height),
+            _ => Maze::with_floors(width, height, floors),
+        };
+
+        let holes = &root["holes"];
+        if !holes.is_null() && !holes.is_array() {
+            return Err(invalid("holes", "expected an array"));
+        }
+        if !holes.is_empty() {
+            if floors > 1 || topology == Topology::Polar {
+                return Err(invalid(
+                    "holes",
+                    "only square and hex mazes with one floor can have holes",
+                ));
+            }
+            let mut mask = Mask::new(width, height);
+            for (i, hole) in holes.members().enumerate() {
+                let (x, y) = maze.coords(cell(&maze, hole, &format!("holes[{i}]"))?);
+                mask.set(x, y, false);
+            }
+            if mask.enabled_count() == 0 {
+                return Err(invalid("holes", "every cell is a hole"));
+            }
+            maze = Maze::with_mask(topology, &mask);
+        }
+
+        let crossings = &root["crossings"];
+        if !crossings.is_null() && !crossings.is_array() {
+            return Err(invalid("crossings", "expected an array"));
+        }
+        for (i, crossing) in crossings.members().enumerate() {
+            let path = format!("crossings[{i}]");
+            let cell = cell(&maze, &crossing["cell"], &format!("{path}.cell"))?;
+            let along = match crossing["tunnel"].as_str() {
+                Some("east-west") => Direction::East,
+                Some("north-south") => Direction::South,
+                _ => {
+                    return Err(invalid(
+                        format!("{path}.tunnel"),
+                        "expected \"east-west\" or \"north-south\"",
+                    ))
+                }
+            };
+            if maze.add_tunnel(cell, along).is_none() {
+                return Err(invalid(path, "there is no room for a tunnel"));
+            }
+        }
+
+        let masks = walls
+            .members()
+            .enumerate()
+            .map(|(i, mask)| {
+                let sides = maze.sides(i).fold(0, |sides, dir| sides | dir.bit());
+                mask.as_u16()
+                    .filter(|&mask| mask & !sides == 0)
+                    .ok_or_else(|| {
+                        invalid(
+                            format!("walls[{i}]"),
+                            "expected a bitmask of the cell's sides",
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for cell in maze.cells() {
+            for dir in maze.sides(cell) {
+                if masks[cell] & dir.bit() != 0 {
+                    continue;
+                }
+                let consistent = maze
+                    .neighbor(cell, dir)
+                    .zip(maze.back(cell, dir))
+                    .is_some_and(|(n, back)| masks[n] & back.bit() == 0);
+                if !consistent {
+                    return Err(invalid(
+                        format!("walls[{cell}]"),
+                        format!("{dir:?} side is open but the other side is walled"),
+                    ));
+                }
+                maze.carve(cell, dir);
+            }
+        }
+        if let Some(cell) = maze
+            .cells()
+            .find(|&c| maze.is_open(c, Direction::Up) && maze.ramp(c).is_none())
+        {
+            return Err(invalid(
+                format!("walls[{cell}]"),
+                "a passage up needs a dead end on both floors to make a ramp",
+            ));
+        }
+        let mut ramps = Vec::new();
+        for (i, crossing) in crossings.members().enumerate() {
+            let crossing = cell(&maze, &crossing["cell"], "")?;
+            match maze.bridge(crossing) {
+                Some(bridge) if bridge.iter().all(|(ramp, _)| !ramps.contains(ramp)) => {
+                    ramps.extend(bridge.map(|(ramp, _)| ramp));
+                }
+                _ => {
+                    return Err(invalid(
+                        format!("crossings[{i}]"),
+                        "the cells at both ends of the bridge need room for a ramp",
+                    ))
+                }
+            }
+        }
+
+        let start = cell(&maze, &root["start"], "start")?;
+        maze.set_start(start);
+        let goal = cell(&maze, &root["goal"], "goal")?;
+        maze.set_goal(goal);
+
+        let mut items = Vec::new();
+        let list = &root["items"];
+        if !list.is_null() && !list.is_array() {
+            return Err(invalid("items", "expected an array"));
+        }
+        for (i, item) in list.members().enumerate() {
+            let path = format!("items[{i}]");
+            items.push(Item {
+                kind: string(item, "kind", &path)?,
+                cell: cell(&maze, &item["cell"], &format!("{path}.cell"))?,
+            });
+        }
+
+        let par_time = match &root["par_time"] {
+            JsonValue::Null => None,
+            value => Some(
+                value
+                    .as_f64()
+                    .filter(|&t| t.is_finite() && t >= 0.0)
+                    .ok_or_else(|| invalid("par_time", "expected a non-negative number"))?,
+            ),
+        };
+
+        Ok(MazeLevel {
+            maze,
+            items,
+            theme: string(&root, "theme", "")?,
+            meta: LevelMeta {
+                name: string(&root, "name", "")?,
+                author: string(&root, "author", "")?,
+                par_time,
+            },
+        })
+    }
+}
+
+/// Loads `.maze` files as [`MazeLevel`] assets.
+#[derive(Default)]
+pub struct MazeLevelLoader;
+
+impl AssetLoader for MazeLevelLoader {
+    type Asset = MazeLevel;
+    type Settings = ();
+    type Error = LevelError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<MazeLevel, LevelError>> {
+        Box::pin(async move {
+            let mut source = String::new();
+            reader.read_to_string(&mut source).await?;
+            MazeLevel::from_json(&source)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["maze"]
+    }
+}
+
+/// Asset path of the level to play, relative to the assets folder; a
+/// generated maze is played when there is none.
+#[derive(Resource, Default)]
+pub struct LevelFile(pub Option<String>);
+
+/// The level being played, if it comes from a file.
+#[derive(Resource)]
+pub struct LevelHandle(pub Handle<MazeLevel>);
+
+pub fn load_level(mut commands: Commands, file: Res<LevelFile>, asset_server: Res<AssetServer>) {
+    if let Some(path) = &file.0 {
+        commands.insert_resource(LevelHandle(asset_server.load(path.clone())));
+    }
+}
+
+/// Whether the level file, if any, is done loading, successfully or not.
+pub fn level_settled(level: Option<Res<LevelHandle>>, asset_server: Res<AssetServer>) -> bool {
+    let Some(level) = level else {
+        return true;
+    };
+    match asset_server.load_state(&level.0) {
+        LoadState::Loaded => true,
+        LoadState::Failed => {
+            warn!("level failed to load, playing the generated maze instead");
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Swaps the level into [`CurrentMaze`] when it is loaded or changes on
+/// disk.
+pub fn apply_level(
+    mut events: EventReader<AssetEvent<MazeLevel>>,
+    level: Option<Res<LevelHandle>>,
+    levels: Res<Assets<MazeLevel>>,
+    mut maze: ResMut<CurrentMaze>,
+) {
+    let Some(level) = level else {
+        events.clear();
+        return;
+    };
+    for event in events.read() {
+        if !(event.is_loaded_with_dependencies(&level.0) || event.is_modified(&level.0)) {
+            continue;
+        }
+        if let Some(loaded) = levels.get(&level.0) {
+            info!("playing level \"{}\"", loaded.meta.name);
+            maze.0 = loaded.maze.clone();
+        }
+    }
+}
+
+fn tunnel_name(along: Direction) -> &'static str {
+    match along {
+        Direction::East => "east-west",
+        _ => "north-south",
+    }
+}
+
+fn invalid(field: impl Into<String>, reason: impl Into<String>) -> LevelError {
+    LevelError::Invalid {
+        field: field.into(),
+        reason: reason.into(),
+    }
+}
+
+fn field<'a>(object: &'a JsonValue, name: &str) -> Result<&'a JsonValue, LevelError> {
+    match &object[name] {
+        JsonValue::Null => Err(LevelError::Missing(name.to_owned())),
+        value => Ok(value),
+    }
+}
+
+fn dimension(root: &JsonValue, name: &str) -> Result<usize, LevelError> {
+    field(root, name)?
+        .as_usize()
+        .filter(|&n| n > 0)
+        .ok_or_else(|| invalid(name, "expected a positive integer"))
+}
+
+/// An optional string field of `object`, empty when absent.
+fn string(object: &JsonValue, name: &str, parent: &str) -> Result<String, LevelError> {
+    let path = if parent.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{parent}.{name}")
+    };
+    match &object[name] {
+        JsonValue::Null => Ok(String::new()),
+        value => value
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| invalid(path, "expected a string")),
+    }
+}
+
+/// A cell given as `[x, y]`, or `[x, y, floor]` in mazes with several
+/// floors.
+fn cell(maze: &Maze, value: &JsonValue, path: &str) -> Result<usize, LevelError> {
+    if value.is_null() {
+        return Err(LevelError::Missing(path.to_owned()));
+    }
+    let coords = value
+        .members()
+        .map(JsonValue::as_usize)
+        .collect::<Option<Vec<_>>>()
+        .filter(|coords| value.is_array() && coords.len() == 2 + usize::from(maze.floors() > 1))
+        .ok_or_else(|| match maze.floors() {
+            1 => invalid(path, "expected [x, y]"),
+            _ => invalid(path, "expected [x, y, floor]"),
+        })?;
+    let (x, y) = (coords[0], coords[1]);
+    let floor = coords.get(2).copied().unwrap_or(0);
+    if y >= maze.height() || x >= maze.row_len(y) || floor >= maze.floors() {
+        return Err(invalid(path, format!("{coords:?} is outside the maze")));
+    }
+    let cell = maze.cell_on(floor, x, y);
+    if !maze.is_enabled(cell) {
+        return Err(invalid(path, format!("{coords:?} is a hole")));
+    }
+    Ok(cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{
+        floors::Floors,
+        generate::{self, MazeGenerator},
+        mask::{Mask, Regions},
+        weave::Weave,
+    };
+
+    fn sample() -> MazeLevel {
+        let mut maze = generate::recursive_backtracker(7, 5, 11);
+        maze.set_start(maze.cell(3, 2));
+        MazeLevel {
+            items: vec![
+                Item {
+                    kind: "coin".into(),
+                    cell: maze.cell(1, 4),
+                },
+                Item {
+                    kind: "key".into(),
+                    cell: maze.cell(6, 0),
+                },
+            ],
+            maze,
+            theme: "stone".into(),
+            meta: LevelMeta {
+                name: "Sample \"level\"".into(),
+                author: "maze team".into(),
+                par_time: Some(42.25),
+            },
+        }
+    }
+
+    fn error_field(source: &str) -> String {
+        match MazeLevel::from_json(source) {
+            Err(LevelError::Missing(field)) | Err(LevelError::Invalid { field, .. }) => field,
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    fn round_trips_exactly() {
+        let level = sample();
+        let saved = level.to_json();
+        let loaded = MazeLevel::from_json(&saved).unwrap();
+        assert_eq!(loaded, level);
+        assert_eq!(loaded.to_json(), saved);
+
+        let bare = MazeLevel::new(Maze::new(3, 2));
+        assert_eq!(MazeLevel::from_json(&bare.to_json()).unwrap(), bare);
+
+        let hex = MazeLevel::new(generate::Kruskal.generate_with(Topology::Hex, 5, 4, 2));
+        assert_eq!(MazeLevel::from_json(&hex.to_json()).unwrap(), hex);
+
+        let polar = MazeLevel::new(generate::Wilson.generate_with(Topology::Polar, 5, 6, 2));
+        assert_eq!(MazeLevel::from_json(&polar.to_json()).unwrap(), polar);
+
+        let mut tower = MazeLevel::new(Floors::default().generate(&generate::Kruskal, 4, 3, 2));
+        tower.items.push(Item {
+            kind: "coin".into(),
+            cell: tower.maze.cell_on(1, 3, 2),
+        });
+        let saved = tower.to_json();
+        assert_eq!(MazeLevel::from_json(&saved).unwrap(), tower);
+
+        let weave = MazeLevel::new(Weave::default().generate(9, 8, 5));
+        assert!(weave.maze.tunnel_count() > 0);
+        assert_eq!(MazeLevel::from_json(&weave.to_json()).unwrap(), weave);
+
+        let mask = Mask::from_text("###.\n#.##\n####").unwrap();
+        for topology in [Topology::Square, Topology::Hex] {
+            let maze = mask
+                .generate(&generate::Prim, topology, 3, Regions::Reject)
+                .unwrap();
+            let masked = MazeLevel::new(maze);
+            assert_eq!(MazeLevel::from_json(&masked.to_json()).unwrap(), masked);
+        }
+    }
+
+    #[test]
+    fn reads_the_documented_example() {
+        let level = MazeLevel::from_json(
+            r#"{
+                "version": 1, "name": "Tutorial", "author": "maze team",
+                "par_time": 30, "theme": "stone", "width": 2, "height": 1,
+                "walls": [13, 7], "start": [0, 0], "goal": [1, 0],
+                "items": [{ "kind": "coin", "cell": [1, 0] }]
+            }"#,
+        )
+        .unwrap();
+        assert!(level.maze.is_open(0, Direction::East));
+        assert_eq!(level.maze.goal(), 1);
+        assert_eq!(level.meta.par_time, Some(30.0));
+        assert_eq!(level.items[0].cell, 1);
+    }
+
+    #[test]
+    fn errors_name_the_bad_field() {
+        let good = json::parse(&sample().to_json()).unwrap();
+        let with = |name: &str, value: JsonValue| {
+            let mut root = good.clone();
+            root[name] = value;
+            root.dump()
+        };
+        let without = |name: &str| {
+            let mut root = good.clone();
+            root.remove(name);
+            root.dump()
+        };
+
+        assert_eq!(error_field(&without("width")), "width");
+        assert_eq!(error_field(&with("height", 0.into())), "height");
+        assert_eq!(error_field(&without("walls")), "walls");
+        assert_eq!(error_field(&with("walls", vec![15; 3].into())), "width");
+        assert_eq!(error_field(&with("walls", 15.into())), "walls");
+        assert_eq!(error_field(&without("goal")), "goal");
+        assert_eq!(error_field(&with("start", vec![7, 0].into())), "start");
+        assert_eq!(error_field(&with("theme", 3.into())), "theme");
+        assert_eq!(error_field(&with("par_time", "soon".into())), "par_time");
+        assert_eq!(
+            error_field(&with("topology", "triangle".into())),
+            "topology"
+        );
+
+        let mut walls = good["walls"].clone();
+        // A hex side on a square cell.
+        walls[4] = 16.into();
+        assert_eq!(error_field(&with("walls", walls)), "walls[4]");
+
+        let mut polar =
+            json::parse(&MazeLevel::new(Maze::with_topology(Topology::Polar, 3, 3)).to_json())
+                .unwrap();
+        polar["width"] = 9.into();
+        assert_eq!(error_field(&polar.dump()), "width");
+        polar["width"] = 3.into();
+        polar["height"] = 4.into();
+        assert_eq!(error_field(&polar.dump()), "width");
+        polar["height"] = 3.into();
+        polar["floors"] = 2.into();
+        assert_eq!(error_field(&polar.dump()), "floors");
+
+        // Sizes far beyond the walls are turned down before anything is
+        // allocated for them, even where the cell count overflows.
+        for (width, height) in [(100_000u64, 100_000u64), (1 << 32, 1 << 32)] {
+            let huge = with("width", width.into());
+            let mut huge = json::parse(&huge).unwrap();
+            huge["height"] = height.into();
+            assert_eq!(error_field(&huge.dump()), "width");
+        }
+        polar["floors"] = JsonValue::Null;
+        polar["height"] = usize::MAX.into();
+        assert_eq!(error_field(&polar.dump()), "width");
+
+        let masked = MazeLevel::new(Maze::with_mask(
+            Topology::Square,
+            &Mask::from_text("##\n.#").unwrap(),
+        ));
+        let mut masked = json::parse(&masked.to_json()).unwrap();
+        masked["start"] = vec![0, 1].into();
+        assert_eq!(error_field(&masked.dump()), "start");
+        masked["holes"] = vec![vec![0, 0], vec![1, 0], vec![0, 1], vec![1, 1]].into();
+        assert_eq!(error_field(&masked.dump()), "holes");
+
+        let mut item = good["items"].clone();
+        item[1]["cell"] = "here".into();
+        assert_eq!(error_field(&with("items", item)), "items[1].cell");
+
+        assert!(matches!(
+            MazeLevel::from_json(&with("version", 2.into())),
+            Err(LevelError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            MazeLevel::from_json("{ nope"),
+            Err(LevelError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn bundled_levels_load() {
+        for (source, name) in [
+            (include_str!("../assets/levels/tutorial.maze"), "Tutorial"),
+            (include_str!("../assets/levels/honeycomb.maze"), "Honeycomb"),
+            (include_str!("../assets/levels/labyrinth.maze"), "Labyrinth"),
+            (include_str!("../assets/levels/tower.maze"), "Tower"),
+        ] {
+            let level = MazeLevel::from_json(source).unwrap();
+            assert_eq!(level.meta.name, name);
+            assert!(
+                crate::maze::solve::distances(&level.maze, level.maze.start())
+                    .iter()
+                    .all(Option::is_some)
+            );
+        }
+    }
+
+    #[test]
+    fn passages_up_must_be_ramps() {
+        // Two floors of three cells with a passage up from the middle one,
+        // which is entered from the west.
+        let source = |middle: u16, east: u16| {
+            format!(
+                r#"{{ "version": 1, "width": 3, "height": 1, "floors": 2,
+                "walls": [269, 7, 271, 527, {middle}, {east}],
+                "start": [0, 0, 0], "goal": [2, 0, 1] }}"#
+            )
+        };
+        // Above, the passage leads nowhere.
+        assert_eq!(error_field(&source(15, 527)), "walls[1]");
+        let level = MazeLevel::from_json(&source(13, 519)).unwrap();
+        assert_eq!(level.maze.ramp(1), Some(Direction::East));
+    }
+
+    #[test]
+    fn bridges_need_ramps() {
+        // A plus-shaped weave: the passage from the west runs under the
+        // middle cell, the one from the north over it.
+        let source = |corner: u16, north: u16| {
+            format!(
+                r#"{{ "version": 1, "width": 3, "height": 3,
+                "crossings": [{{ "cell": [1, 1], "tunnel": "east-west" }}],
+                "walls": [{corner}, {north}, 15, 13, 0, 7, 15, 14, 15, 0],
+                "start": [0, 1], "goal": [2, 1] }}"#
+            )
+        };
+        let level = MazeLevel::from_json(&source(15, 11)).unwrap();
+        assert_eq!(level.maze.tunnel_under(4), Some(9));
+        assert!(level.maze.is_open(3, Direction::East));
+        // The cell north of the bridge turns west.
+        assert_eq!(error_field(&source(13, 3)), "crossings[0]");
+    }
+
+    #[test]
+    fn one_sided_walls_are_rejected() {
+        // Cell 0 claims an open east side, cell 1 a walled west side.
+        let source = r#"{ "version": 1, "width": 2, "height": 1,
+            "walls": [13, 15], "start": [0, 0], "goal": [1, 0] }"#;
+        assert_eq!(error_field(source), "walls[0]");
+    }
+}
@@ -1,4 +1,15 @@
-pub mod effect;
+pub mod action;
 pub mod fps;
+pub mod game;
+pub mod gamepad;
+pub mod ground;
+pub mod hint;
+pub mod infinite;
 pub mod input;
+pub mod level;
+pub mod maze;
 pub mod output;
+pub mod picture;
+pub mod rebind;
+pub mod stream;
+pub mod world;
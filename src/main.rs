@@ -4,39 +4,137 @@
 // type aliases tends to obfuscate code while offering no improvement in code cleanliness.
 #![allow(clippy::type_complexity)]
 
-use bevy::{pbr::PointLightShadowMap, prelude::*};
+use bevy::{
+    asset::AssetPlugin, input::InputSystem, pbr::PointLightShadowMap, prelude::*, ui::UiSystem,
+};
 use bevy_xpbd_3d::prelude::*;
 
 #[cfg(not(all(feature = "webgl2", target_arch = "wasm32")))]
 use bevy::core_pipeline::experimental::taa::TemporalAntiAliasPlugin;
 
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
-use maze::{effect, fps, input, output};
+use maze::{
+    action, fps,
+    game::{self, GameState},
+    gamepad, ground, hint, infinite, input, level, output, rebind, stream, world,
+};
 
 fn main() {
     let mut app = App::new();
+    // `--endless` in place of a level file plays an endless maze instead,
+    // `--infinite` one that only grows southwards.
+    let level = std::env::args().nth(1);
+    let endless = level.as_deref() == Some("--endless");
+    let infinite = level.as_deref() == Some("--infinite");
 
     app.add_plugins((
-        DefaultPlugins,
+        DefaultPlugins.set(AssetPlugin {
+            // Rebuild the maze when its level file is edited.
+            watch_for_changes_override: Some(true),
+            ..default()
+        }),
         PhysicsPlugins::default(),
         FrameTimeDiagnosticsPlugin::default(),
     ))
+    .insert_resource(action::Bindings::load_or_default(action::BINDINGS_FILE))
+    .init_resource::<Input<action::Action>>()
+    .init_resource::<gamepad::StickSettings>()
+    .init_resource::<gamepad::Sticks>()
+    .init_resource::<rebind::Rebinding>()
+    .init_resource::<world::CurrentMaze>()
+    .init_resource::<world::MazeLayout>()
+    .init_resource::<world::WallSpawning>()
+    .init_resource::<world::MazeMaterials>()
+    .init_resource::<game::LevelProgress>()
+    .add_state::<GameState>()
+    .add_event::<game::MazeWon>()
+    .init_resource::<hint::HintSettings>()
+    .init_resource::<hint::HintCooldown>()
+    .add_event::<hint::HintRequested>()
+    .init_asset::<level::MazeLevel>()
+    .init_asset_loader::<level::MazeLevelLoader>()
+    .insert_resource(level::LevelFile(level.filter(|_| !endless && !infinite)))
     .insert_resource(ClearColor(Color::BLACK))
     .insert_resource(PointLightShadowMap { size: 2048 })
     .insert_resource(AmbientLight {
         brightness: 0.0,
         ..default()
     })
-    .add_systems(Startup, (output::setup, fps::setup_fps_counter))
+    .add_systems(
+        Startup,
+        (
+            output::setup,
+            level::load_level,
+            fps::setup_fps_counter,
+            rebind::setup_controls_panel,
+        ),
+    )
+    .add_systems(
+        PreUpdate,
+        (
+            // Clicks are told apart by the hover state of this frame.
+            (
+                rebind::capture_binding.after(UiSystem::Focus),
+                action::read_actions,
+            )
+                .chain(),
+            gamepad::read_gamepads,
+        )
+            .after(InputSystem),
+    )
     .add_systems(
         Update,
         (
-            input::deal_input,
-            effect::flicker_system,
+            (
+                level::apply_level,
+                world::spawn_maze.run_if(
+                    resource_changed::<world::CurrentMaze>()
+                        .and_then(not(resource_exists::<stream::ChunkStreaming>()))
+                        .and_then(not(resource_exists::<infinite::InfiniteMaze>())),
+                ),
+                game::restart_level.run_if(resource_changed::<world::CurrentMaze>()),
+                game::finish_loading
+                    .run_if(in_state(GameState::Loading).and_then(level::level_settled)),
+            )
+                .chain(),
+            (
+                ground::detect_ground,
+                input::deal_input,
+                input::ease_camera_height,
+                // Endless mazes have no start or goal to play for.
+                (game::track_progress, game::detect_goal)
+                    .chain()
+                    .run_if(not(resource_exists::<stream::ChunkStreaming>())),
+                hint::show_hint.run_if(not(resource_exists::<stream::ChunkStreaming>())),
+                hint::fade_breadcrumbs,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+            stream::stream_chunks.run_if(resource_exists::<stream::ChunkStreaming>()),
+            (infinite::grow_maze, infinite::show_distance)
+                .chain()
+                .run_if(resource_exists::<infinite::InfiniteMaze>()),
+            game::toggle_pause,
+            game::show_results.run_if(not(resource_exists::<stream::ChunkStreaming>())),
             fps::fps_text_update_system,
             fps::fps_counter_showhide,
+            (
+                rebind::toggle_controls_panel,
+                rebind::click_bindings,
+                rebind::update_binding_labels,
+            )
+                .chain(),
         ),
-    );
+    )
+    .add_systems(OnEnter(GameState::Paused), game::pause_physics)
+    .add_systems(OnExit(GameState::Paused), game::resume_physics);
+
+    if endless {
+        app.init_resource::<stream::ChunkStreaming>();
+    }
+    if infinite {
+        app.init_resource::<infinite::InfiniteMaze>();
+    }
 
     // *Note:* TAA is not _required_ for specular transmission, but
     // it _greatly enhances_ the look of the resulting blur effects.
@@ -0,0 +1,387 @@
+//! Text renderings of mazes.
+//!
+//! The ASCII form is a grid of `2 * width + 1` by `2 * height + 1`
+//! characters in which every cell, every wall position and every corner
+//! gets one character:
+//!
+//! ```text
+//! #########
+//! #S..#...#
+//! ###.#.#.#
+//! #.....#G#
+//! #########
+//! ```
+//!
+//! `#` is a wall, `.` floor, `S` the start and `G` the goal. Cells sit at
+//! odd rows and columns; corners are always walls. Without an `S` or `G`
+//! the start and goal default to the first and last cell like
+//! [`Maze::new`]. Only square mazes with a single floor and neither
+//! tunnels nor holes can be written as text, see [`Maze::check_plain`].
+
+use std::fmt;
+
+use super::{
+    runs::{horizontal_wall, vertical_wall},
+    Direction, Maze, Unsupported,
+};
+
+/// Where and why [`Maze::from_ascii`] failed. Lines and columns count
+/// from 1, columns in characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// There is no grid at all.
+    Empty,
+    /// A character other than `#`, `.`, `S` or `G`.
+    UnexpectedChar(char),
+    /// A line is shorter or longer than the first one.
+    LineLength { expected: usize, found: usize },
+    /// The grid is not an odd number of characters, at least 3, across or
+    /// down.
+    BadSize,
+    /// A corner between cells is not a wall.
+    OpenCorner,
+    /// A cell is a wall.
+    WalledCell,
+    /// The outer wall has a gap.
+    OpenBorder,
+    /// `S` or `G` is placed where a wall goes.
+    MarkerOnWall(char),
+    /// `S` or `G` appears more than once.
+    DuplicateMarker(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Empty => write!(f, "no maze found"),
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {c:?}"),
+            ParseErrorKind::LineLength { expected, found } => {
+                write!(f, "line is {found} characters long, expected {expected}")
+            }
+            ParseErrorKind::BadSize => write!(f, "the grid must be an odd size, at least 3"),
+            ParseErrorKind::OpenCorner => write!(f, "corners must be walls"),
+            ParseErrorKind::WalledCell => write!(f, "cells cannot be walls"),
+            ParseErrorKind::OpenBorder => write!(f, "the outer wall has a gap"),
+            ParseErrorKind::MarkerOnWall(c) => write!(f, "{c:?} must be on a cell"),
+            ParseErrorKind::DuplicateMarker(c) => write!(f, "{c:?} appears more than once"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Maze {
+    pub fn from_ascii(source: &str) -> Result<Maze, ParseError> {
+        let error = |line: usize, column: usize, kind| ParseError {
+            line: line + 1,
+            column: column + 1,
+            kind,
+        };
+
+        let mut rows = source
+            .lines()
+            .map(|line| line.trim_end().chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        while rows.last().is_some_and(Vec::is_empty) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            return Err(error(0, 0, ParseErrorKind::Empty));
+        }
+        let columns = rows[0].len();
+        for (r, row) in rows.iter().enumerate() {
+            if let Some(c) = row.iter().position(|c| !"#.SG".contains(*c)) {
+                return Err(error(r, c, ParseErrorKind::UnexpectedChar(row[c])));
+            }
+            if row.len() != columns {
+                let kind = ParseErrorKind::LineLength {
+                    expected: columns,
+                    found: row.len(),
+                };
+                return Err(error(r, row.len().min(columns), kind));
+            }
+        }
+        if columns < 3 || columns % 2 == 0 {
+            return Err(error(0, columns.saturating_sub(1), ParseErrorKind::BadSize));
+        }
+        if rows.len() < 3 || rows.len() % 2 == 0 {
+            return Err(error(rows.len() - 1, 0, ParseErrorKind::BadSize));
+        }
+
+        let mut maze = Maze::new(columns / 2, rows.len() / 2);
+        let (mut start, mut goal) = (None, None);
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &ch) in row.iter().enumerate() {
+                let kind = match (r % 2, c % 2) {
+                    (0, 0) if ch != '#' => ParseErrorKind::OpenCorner,
+                    (0, 0) => continue,
+                    (1, 1) => {
+                        let cell = maze.cell(c / 2, r / 2);
+                        let marker = match ch {
+                            '#' => return Err(error(r, c, ParseErrorKind::WalledCell)),
+                            'S' => &mut start,
+                            'G' => &mut goal,
+                            _ => continue,
+                        };
+                        if marker.replace(cell).is_some() {
+                            return Err(error(r, c, ParseErrorKind::DuplicateMarker(ch)));
+                        }
+                        continue;
+                    }
+                    _ => match ch {
+                        '#' => continue,
+                        'S' | 'G' => ParseErrorKind::MarkerOnWall(ch),
+                        _ if r == 0 || c == 0 || r == rows.len() - 1 || c == columns - 1 => {
+                            ParseErrorKind::OpenBorder
+                        }
+                        _ => {
+                            // Carve from the cell to the west or north of the gap.
+                            let (cell, dir) = if r % 2 == 1 {
+                                (maze.cell(c / 2 - 1, r / 2), Direction::East)
+                            } else {
+                                (maze.cell(c / 2, r / 2 - 1), Direction::South)
+                            };
+                            maze.carve(cell, dir);
+                            continue;
+                        }
+                    },
+                };
+                return Err(error(r, c, kind));
+            }
+        }
+        if let Some(start) = start {
+            maze.set_start(start);
+        }
+        if let Some(goal) = goal {
+            maze.set_goal(goal);
+        }
+        Ok(maze)
+    }
+
+    /// The ASCII form described in the [module docs](self). When start and
+    /// goal share a cell only the `S` is written.
+    pub fn to_ascii(&self) -> Result<String, Unsupported> {
+        self.check_plain()?;
+        let mut out = String::new();
+        for line in 0..=self.height() {
+            for x in 0..self.width() {
+                out.push('#');
+                out.push(if horizontal_wall(self, x, line) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push_str("#\n");
+            if line == self.height() {
+                break;
+            }
+            for x in 0..=self.width() {
+                out.push(if vertical_wall(self, x, line) {
+                    '#'
+                } else {
+                    '.'
+                });
+                if x < self.width() {
+                    out.push(self.marker(self.cell(x, line)).unwrap_or('.'));
+                }
+            }
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Draws the maze with Unicode box-drawing characters for terminals,
+    /// three columns per cell.
+    pub fn to_box_drawing(&self) -> Result<String, Unsupported> {
+        self.check_plain()?;
+        // Indexed by the arms leaving a corner: 1 up, 2 right, 4 down, 8 left.
+        const CORNERS: [char; 16] = [
+            ' ', '╵', '╶', '└', '╷', '│', '┌', '├', '╴', '┘', '─', '┴', '┐', '┤', '┬', '┼',
+        ];
+        let (width, height) = (self.width(), self.height());
+        let mut out = String::new();
+        for line in 0..=height {
+            for x in 0..=width {
+                let mut arms = 0;
+                if line > 0 && vertical_wall(self, x, line - 1) {
+                    arms |= 1;
+                }
+                if x < width && horizontal_wall(self, x, line) {
+                    arms |= 2;
+                }
+                if line < height && vertical_wall(self, x, line) {
+                    arms |= 4;
+                }
+                if x > 0 && horizontal_wall(self, x - 1, line) {
+                    arms |= 8;
+                }
+                out.push(CORNERS[arms]);
+                if x < width {
+                    out.push_str(if arms & 2 != 0 { "───" } else { "   " });
+                }
+            }
+            out.push('\n');
+            if line == height {
+                break;
+            }
+            for x in 0..=width {
+                out.push(if vertical_wall(self, x, line) {
+                    '│'
+                } else {
+                    ' '
+                });
+                if x < width {
+                    let marker = self.marker(self.cell(x, line)).unwrap_or(' ');
+                    out.extend([' ', marker, ' ']);
+                }
+            }
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    fn marker(&self, cell: usize) -> Option<char> {
+        if cell == self.start() {
+            Some('S')
+        } else if cell == self.goal() {
+            Some('G')
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate, mask::Mask, Topology};
+
+    fn parse_error(source: &str) -> (usize, usize, ParseErrorKind) {
+        let e = Maze::from_ascii(source).unwrap_err();
+        (e.line, e.column, e.kind)
+    }
+
+    #[test]
+    fn round_trips_generated_mazes() {
+        for name in generate::NAMES {
+            let mut maze = generate::by_name(name).unwrap().generate(9, 6, 3);
+            maze.set_start(maze.cell(4, 2));
+            let text = maze.to_ascii().unwrap();
+            assert_eq!(Maze::from_ascii(&text).unwrap(), maze, "{name}");
+        }
+    }
+
+    #[test]
+    fn backtracker_snapshot() {
+        let maze = generate::recursive_backtracker(5, 3, 1);
+        assert_eq!(
+            maze.to_ascii().unwrap(),
+            "\
+###########
+#S#.....#.#
+#.###.#.#.#
+#.....#.#.#
+#######.#.#
+#........G#
+###########
+"
+        );
+        assert_eq!(
+            maze.to_box_drawing().unwrap(),
+            "\
+┌───┬───────────┬───┐
+│ S │           │   │
+│   └───╴   ╷   │   │
+│           │   │   │
+├───────────┘   ╵   │
+│                 G │
+└───────────────────┘
+"
+        );
+    }
+
+    #[test]
+    fn only_plain_mazes_become_text() {
+        let mask = Mask::from_text("##\n.#").unwrap();
+        for (maze, reason) in [
+            (
+                Maze::with_topology(Topology::Hex, 3, 3),
+                Unsupported::Topology(Topology::Hex),
+            ),
+            (Maze::with_floors(2, 2, 2), Unsupported::Floors),
+            (Maze::with_mask(Topology::Square, &mask), Unsupported::Holes),
+        ] {
+            assert_eq!(maze.to_ascii(), Err(reason));
+            assert_eq!(maze.to_box_drawing(), Err(reason));
+        }
+    }
+
+    #[test]
+    fn reads_markers_and_defaults() {
+        let maze = Maze::from_ascii("#####\n#.#G#\n#.#.#\n#S..#\n#####\n").unwrap();
+        assert_eq!(
+            (maze.start(), maze.goal()),
+            (maze.cell(0, 1), maze.cell(1, 0))
+        );
+        assert!(maze.is_open(maze.start(), Direction::East));
+        assert!(maze.has_wall(0, Direction::East));
+
+        let maze = Maze::from_ascii("#####\r\n#...#   \r\n#####\r\n\r\n").unwrap();
+        assert_eq!((maze.start(), maze.goal()), (0, 1));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        use ParseErrorKind::*;
+        assert_eq!(parse_error(" \n\n"), (1, 1, Empty));
+        assert_eq!(parse_error("###\n#x#\n###"), (2, 2, UnexpectedChar('x')));
+        assert_eq!(
+            parse_error("#####\n#.#\n#####"),
+            (
+                2,
+                4,
+                LineLength {
+                    expected: 5,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(parse_error("####\n#..#\n####"), (1, 4, BadSize));
+        assert_eq!(parse_error("###\n#.#\n###\n###"), (4, 1, BadSize));
+        assert_eq!(parse_error("#####\n#.###\n#####"), (2, 4, WalledCell));
+        assert_eq!(
+            parse_error("#####\n#...#\n#...#\n#...#\n#####"),
+            (3, 3, OpenCorner)
+        );
+        assert_eq!(parse_error("#####\n....#\n#####\n"), (2, 1, OpenBorder));
+        assert_eq!(
+            parse_error("#####\n#.S.#\n#####"),
+            (2, 3, MarkerOnWall('S'))
+        );
+        assert_eq!(
+            parse_error("#####\n#G#G#\n#####"),
+            (2, 4, DuplicateMarker('G'))
+        );
+
+        let e = Maze::from_ascii("###\n#?#\n###").unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 2: unexpected character '?'");
+    }
+}
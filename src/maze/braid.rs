@@ -0,0 +1,196 @@
+//! Turning perfect mazes into braid mazes.
+//!
+//! A braid maze has loops and few or no dead ends, which defeats simple
+//! wall-following.
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::{disjoint_set::DisjointSet, Direction, Maze};
+
+/// Settings for the braiding pass.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Braid {
+    /// Fraction of dead ends to remove, from 0 (none) to 1 (all).
+    pub dead_end_removal: f64,
+    /// Number of extra walls to knock down at random to add loops.
+    pub extra_loops: usize,
+}
+
+impl Default for Braid {
+    fn default() -> Self {
+        Braid {
+            dead_end_removal: 0.5,
+            extra_loops: 0,
+        }
+    }
+}
+
+impl Braid {
+    /// Braids `maze` in place. The same maze, settings and seed always give
+    /// the same result.
+    pub fn apply(&self, maze: &mut Maze, seed: u64) -> MazeStats {
+        let mut rng = StdRng::seed_from_u64(seed);
+        // Floors are only joined by ramps, which need more than a hole, and
+        // ramps only work in dead ends, so both stay as they are.
+        let can_open = |maze: &Maze, cell: usize, dir: Direction| {
+            let on_ramp =
+                |c: usize| maze.is_open(c, Direction::Up) || maze.is_open(c, Direction::Down);
+            !dir.is_vertical()
+                && maze.has_wall(cell, dir)
+                && !on_ramp(cell)
+                && maze.neighbor(cell, dir).is_some_and(|n| !on_ramp(n))
+        };
+
+        let mut dead_ends = maze
+            .cells()
+            .filter(|&cell| maze.degree(cell) == 1)
+            .collect::<Vec<_>>();
+        dead_ends.shuffle(&mut rng);
+        let removals = (dead_ends.len() as f64 * self.dead_end_removal.clamp(0.0, 1.0)).round();
+        for &cell in dead_ends.iter().take(removals as usize) {
+            // An earlier removal may have already opened this one up.
+            if maze.degree(cell) != 1 {
+                continue;
+            }
+            let mut walled = maze
+                .neighbors(cell)
+                .filter(|&(dir, _)| can_open(maze, cell, dir))
+                .collect::<Vec<_>>();
+            walled.shuffle(&mut rng);
+            // Joining two dead ends removes both with a single passage.
+            walled.sort_by_key(|&(_, n)| maze.degree(n) != 1);
+            if let Some(&(dir, _)) = walled.first() {
+                maze.carve(cell, dir);
+            }
+        }
+
+        let mut walls = maze
+            .cells()
+            .flat_map(|cell| maze.neighbors(cell).map(move |(dir, _)| (cell, dir)))
+            .filter(|&(cell, dir)| can_open(maze, cell, dir))
+            .collect::<Vec<_>>();
+        walls.shuffle(&mut rng);
+        let mut added = 0;
+        for (cell, dir) in walls {
+            if added == self.extra_loops {
+                break;
+            }
+            // Each inner wall is listed from both sides.
+            if maze.has_wall(cell, dir) {
+                maze.carve(cell, dir);
+                added += 1;
+            }
+        }
+
+        MazeStats::of(maze)
+    }
+}
+
+/// Structural statistics of a maze.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MazeStats {
+    /// Cells with exactly one passage.
+    pub dead_ends: usize,
+    /// Cells with three or more passages.
+    pub junctions: usize,
+    /// Independent loops, i.e. how many passages could be walled up again
+    /// without disconnecting anything.
+    pub loops: usize,
+}
+
+impl MazeStats {
+    pub fn of(maze: &Maze) -> Self {
+        let mut sets = DisjointSet::new(maze.cell_count());
+        let mut loops = 0;
+        for cell in maze.cells() {
+            for (_, next) in maze.links(cell) {
+                if cell < next && !sets.union(cell, next) {
+                    loops += 1;
+                }
+            }
+        }
+        MazeStats {
+            dead_ends: maze.cells().filter(|&c| maze.degree(c) == 1).count(),
+            junctions: maze.cells().filter(|&c| maze.degree(c) >= 3).count(),
+            loops,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generate::{self, MazeGenerator};
+
+    #[test]
+    fn perfect_mazes_have_no_loops() {
+        for name in generate::NAMES {
+            let maze = generate::by_name(name).unwrap().generate(15, 15, 7);
+            let stats = MazeStats::of(&maze);
+            assert_eq!(stats.loops, 0, "{name}");
+            assert!(stats.dead_ends > 0, "{name}");
+        }
+    }
+
+    #[test]
+    fn full_braid_removes_every_dead_end() {
+        for name in generate::NAMES {
+            let mut maze = generate::by_name(name).unwrap().generate(15, 15, 7);
+            let stats = Braid {
+                dead_end_removal: 1.0,
+                extra_loops: 0,
+            }
+            .apply(&mut maze, 1);
+            assert_eq!(stats.dead_ends, 0, "{name}");
+            assert!(stats.loops > 0, "{name}");
+        }
+    }
+
+    #[test]
+    fn partial_braid_keeps_some_dead_ends() {
+        let mut maze = generate::recursive_backtracker(20, 20, 3);
+        let before = MazeStats::of(&maze);
+        let after = Braid {
+            dead_end_removal: 0.5,
+            extra_loops: 0,
+        }
+        .apply(&mut maze, 3);
+        assert!(after.dead_ends < before.dead_ends);
+        assert!(after.dead_ends > 0);
+    }
+
+    #[test]
+    fn extra_loops_are_added() {
+        let mut maze = generate::recursive_backtracker(10, 10, 3);
+        let stats = Braid {
+            dead_end_removal: 0.0,
+            extra_loops: 5,
+        }
+        .apply(&mut maze, 3);
+        assert_eq!(stats.loops, 5);
+    }
+
+    #[test]
+    fn braiding_is_deterministic() {
+        let braid = Braid {
+            dead_end_removal: 0.7,
+            extra_loops: 3,
+        };
+        let mut a = generate::Wilson.generate(12, 12, 99);
+        let mut b = a.clone();
+        assert_eq!(braid.apply(&mut a, 5), braid.apply(&mut b, 5));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn braiding_keeps_ramps() {
+        let mut maze = crate::maze::floors::Floors::default().generate(&generate::Prim, 8, 8, 2);
+        let ramps = maze.cells().filter_map(|c| maze.ramp(c)).count();
+        Braid {
+            dead_end_removal: 1.0,
+            extra_loops: 20,
+        }
+        .apply(&mut maze, 2);
+        assert_eq!(maze.cells().filter_map(|c| maze.ramp(c)).count(), ramps);
+    }
+}
@@ -0,0 +1,193 @@
+//! Endless mazes made of chunks that are generated on demand.
+//!
+//! The plane is split into square chunks of `size` by `size` cells, named
+//! by their column and row, which may be negative. Every chunk is a
+//! perfect maze of its own, generated from the seed and its coordinates
+//! alone, so it comes out the same whenever it is generated again. The
+//! border between two neighboring chunks has a single door, which both
+//! chunks work out from the seed and the border in the same way. Every
+//! chunk can be reached from every other that way; loops only run through
+//! several chunks.
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::{generate::MazeGenerator, wall_runs, Direction, Maze, WallRun};
+
+/// Settings shared by all chunks of an endless maze.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunks {
+    pub seed: u64,
+    /// Edge length of a chunk in cells.
+    pub size: usize,
+}
+
+/// One chunk of an endless maze, see [`Chunks::chunk`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    /// Column and row of the chunk.
+    pub coord: (i32, i32),
+    pub maze: Maze,
+    /// The doors into the neighboring chunks, as the cell along the border
+    /// and the side it opens, clockwise from north.
+    pub doors: [(usize, Direction); 4],
+}
+
+/// What a seed is mixed with, so that chunks and borders at the same
+/// coordinates get unrelated numbers.
+#[derive(Clone, Copy)]
+enum Part {
+    Maze,
+    SouthBorder,
+    EastBorder,
+}
+
+impl Chunks {
+    /// Generates the chunk at column `x`, row `y` with `generator`. The
+    /// coordinates wrap around at the ends of their range.
+    pub fn chunk(&self, generator: &dyn MazeGenerator, (x, y): (i32, i32)) -> Chunk {
+        assert!(self.size > 0, "chunks must have at least one cell");
+        let mut maze = Maze::new(self.size, self.size);
+        let seed = self.mix(Part::Maze, x, y);
+        generator.carve(&mut maze, &mut StdRng::seed_from_u64(seed));
+        let last = self.size - 1;
+        let doors = [
+            (
+                maze.cell(self.door(Part::SouthBorder, x, y.wrapping_sub(1)), 0),
+                Direction::North,
+            ),
+            (
+                maze.cell(last, self.door(Part::EastBorder, x, y)),
+                Direction::East,
+            ),
+            (
+                maze.cell(self.door(Part::SouthBorder, x, y), last),
+                Direction::South,
+            ),
+            (
+                maze.cell(0, self.door(Part::EastBorder, x.wrapping_sub(1), y)),
+                Direction::West,
+            ),
+        ];
+        Chunk {
+            coord: (x, y),
+            maze,
+            doors,
+        }
+    }
+
+    /// Where along the south or east border of the chunk at `x`, `y` its
+    /// door is.
+    fn door(&self, border: Part, x: i32, y: i32) -> usize {
+        (self.mix(border, x, y) % self.size as u64) as usize
+    }
+
+    fn mix(&self, part: Part, x: i32, y: i32) -> u64 {
+        [part as u32, x as u32, y as u32]
+            .into_iter()
+            .fold(self.seed, |hash, value| {
+                split_mix(hash ^ split_mix(u64::from(value)))
+            })
+    }
+}
+
+impl Chunk {
+    /// The walls of the chunk merged into runs like [`wall_runs`], with gaps
+    /// for the doors. The borders to the south and east are left to the
+    /// chunks there, so that every wall belongs to a single chunk.
+    pub fn wall_runs(&self) -> Vec<WallRun> {
+        let size = self.maze.width();
+        let mut runs = Vec::new();
+        for run in wall_runs(&self.maze, size) {
+            if run.line == size {
+                continue;
+            }
+            let door = self.doors.iter().find_map(|&(cell, dir)| {
+                let (x, y) = self.maze.coords(cell);
+                match dir {
+                    Direction::North if run.horizontal => Some(x),
+                    Direction::West if !run.horizontal => Some(y),
+                    _ => None,
+                }
+            });
+            match door {
+                Some(at) if run.line == 0 && (run.from..run.from + run.len).contains(&at) => {
+                    let before = WallRun {
+                        len: at - run.from,
+                        ..run
+                    };
+                    let after = WallRun {
+                        from: at + 1,
+                        len: run.from + run.len - at - 1,
+                        ..run
+                    };
+                    runs.extend([before, after].into_iter().filter(|r| r.len > 0));
+                }
+                _ => runs.push(run),
+            }
+        }
+        runs
+    }
+}
+
+/// The SplitMix64 finalizer, which scatters nearby inputs all over the
+/// range. Unlike the standard library's hashers it is guaranteed to stay
+/// the same, and with it every chunk.
+fn split_mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generate::{Kruskal, RecursiveBacktracker};
+
+    const CHUNKS: Chunks = Chunks { seed: 7, size: 6 };
+
+    #[test]
+    fn chunks_are_perfect_and_repeatable() {
+        for coord in [(0, 0), (1, 0), (-3, 5), (i32::MAX, i32::MIN)] {
+            let chunk = CHUNKS.chunk(&Kruskal, coord);
+            assert_eq!(chunk.maze.passage_count(), 6 * 6 - 1);
+            assert_eq!(CHUNKS.chunk(&Kruskal, coord), chunk);
+        }
+        assert_ne!(
+            CHUNKS.chunk(&Kruskal, (0, 1)).maze,
+            CHUNKS.chunk(&Kruskal, (1, 0)).maze
+        );
+        let reseeded = Chunks { seed: 8, ..CHUNKS };
+        assert_ne!(
+            reseeded.chunk(&Kruskal, (0, 0)),
+            CHUNKS.chunk(&Kruskal, (0, 0))
+        );
+    }
+
+    #[test]
+    fn neighbors_agree_on_their_doors() {
+        let generator = RecursiveBacktracker;
+        for y in -2..2 {
+            for x in -2..2 {
+                let chunk = CHUNKS.chunk(&generator, (x, y));
+                let east = CHUNKS.chunk(&generator, (x + 1, y));
+                let south = CHUNKS.chunk(&generator, (x, y + 1));
+                let coords = |chunk: &Chunk, i: usize| chunk.maze.coords(chunk.doors[i].0);
+                assert_eq!(coords(&chunk, 1).1, coords(&east, 3).1);
+                assert_eq!(coords(&chunk, 2).0, coords(&south, 0).0);
+                for (cell, dir) in chunk.doors {
+                    assert_eq!(chunk.maze.neighbor(cell, dir), None, "{dir:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn runs_leave_the_doors_open() {
+        let chunk = CHUNKS.chunk(&Kruskal, (2, -1));
+        let total = chunk.wall_runs().iter().map(|r| r.len).sum::<usize>();
+        // All walls but the south and east borders and the two doors in
+        // the other two.
+        assert_eq!(total, chunk.maze.walls().count() - 2 * 6 - 2);
+    }
+}
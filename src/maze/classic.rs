@@ -0,0 +1,495 @@
+//! Classic maze solving algorithms, mostly for teaching and visualization.
+//!
+//! Unlike [`super::solve`], these do not compute an answer in one go. Each
+//! one is an iterator producing one step at a time, so a solver can be
+//! animated moving through the maze.
+
+use std::collections::HashSet;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::{Direction, Maze};
+
+/// Which hand a [`WallFollower`] keeps on the wall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Walks the maze keeping one hand on the wall.
+///
+/// Yields every cell it moves into. It finishes at the goal, or as soon as
+/// it would repeat itself, which happens when the goal is not on a wall
+/// connected to the start (e.g. inside a loop). Passages up and down are
+/// walked as the [ramps](Maze::ramp) they are laid out as; any that are not
+/// ramps are left alone.
+pub struct WallFollower<'a> {
+    maze: &'a Maze,
+    hand: Hand,
+    cell: usize,
+    /// The side of the current cell it came in through.
+    entered: Direction,
+    goal: usize,
+    seen: HashSet<(usize, Direction)>,
+}
+
+impl<'a> WallFollower<'a> {
+    pub fn new(maze: &'a Maze, from: usize, to: usize, hand: Hand) -> Self {
+        WallFollower {
+            maze,
+            hand,
+            cell: from,
+            // As if it had walked in facing north.
+            entered: Direction::South,
+            goal: to,
+            seen: HashSet::new(),
+        }
+    }
+
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// The level side of the current cell that walking through its `dir`
+    /// side crosses: a ramp up rises across the cell, and the way down
+    /// from the cell above leads back across the ramp below.
+    fn level(&self, dir: Direction) -> Option<Direction> {
+        match dir {
+            Direction::Up => self.maze.ramp(self.cell),
+            Direction::Down => {
+                let below = self.maze.neighbor(self.cell, Direction::Down)?;
+                self.maze.ramp(below).map(Direction::opposite)
+            }
+            _ => Some(dir),
+        }
+    }
+}
+
+impl Iterator for WallFollower<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.cell == self.goal || !self.seen.insert((self.cell, self.entered)) {
+            return None;
+        }
+        // Level sides are listed clockwise, so the one after the entry
+        // going towards the wall hand is the sharpest turn towards it. Going
+        // all the way round ends with turning back. Up and down take the
+        // place of the level side their ramp is walked through.
+        let sides = self
+            .maze
+            .sides(self.cell)
+            .filter(|dir| !dir.is_vertical())
+            .collect::<Vec<_>>();
+        let entered = self
+            .level(self.entered)
+            .and_then(|entered| sides.iter().position(|&d| d == entered))
+            .unwrap_or(0);
+        let dir = (1..=sides.len())
+            .map(|steps| match self.hand {
+                Hand::Left => (entered + steps) % sides.len(),
+                Hand::Right => (entered + sides.len() - steps) % sides.len(),
+            })
+            .find_map(|i| {
+                [Direction::Up, Direction::Down, sides[i]]
+                    .into_iter()
+                    .find(|&dir| {
+                        self.maze.is_open(self.cell, dir) && self.level(dir) == Some(sides[i])
+                    })
+            })?;
+        self.entered = self.maze.back(self.cell, dir)?;
+        self.cell = self.maze.neighbor(self.cell, dir)?;
+        Some(self.cell)
+    }
+}
+
+/// Trémaux's algorithm.
+///
+/// Marks every passage each time it is walked through and never uses a
+/// passage marked twice. Always finds the goal if it can be reached, and
+/// the passages marked exactly once afterwards form a path back to the
+/// start. Yields every cell it moves into.
+pub struct Tremaux<'a> {
+    maze: &'a Maze,
+    cell: usize,
+    came_from: Option<Direction>,
+    /// Whether the current cell had already been visited before the last
+    /// move.
+    revisited: bool,
+    goal: usize,
+    visited: Vec<bool>,
+    marks: Vec<[u8; 10]>,
+}
+
+impl<'a> Tremaux<'a> {
+    pub fn new(maze: &'a Maze, from: usize, to: usize) -> Self {
+        let mut visited = vec![false; maze.cell_count()];
+        visited[from] = true;
+        Tremaux {
+            maze,
+            cell: from,
+            came_from: None,
+            revisited: false,
+            goal: to,
+            visited,
+            marks: vec![[0; 10]; maze.cell_count()],
+        }
+    }
+
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// How often the passage on the `dir` side of `cell` has been walked.
+    pub fn marks(&self, cell: usize, dir: Direction) -> u8 {
+        self.marks[cell][dir as usize]
+    }
+}
+
+impl Iterator for Tremaux<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.cell == self.goal {
+            return None;
+        }
+        let back = self.came_from;
+        let dir = match back {
+            // Arriving somewhere already visited through a fresh passage
+            // means we went round a loop: turn back.
+            Some(back) if self.revisited && self.marks(self.cell, back) == 1 => back,
+            // Otherwise prefer unmarked passages, then going back.
+            _ => self
+                .maze
+                .sides(self.cell)
+                .filter(|&dir| self.maze.is_open(self.cell, dir))
+                .filter(|&dir| self.marks(self.cell, dir) < 2)
+                .min_by_key(|&dir| (self.marks(self.cell, dir), Some(dir) != back))?,
+        };
+
+        let next = self.maze.neighbor(self.cell, dir)?;
+        let back = self.maze.back(self.cell, dir)?;
+        self.marks[self.cell][dir as usize] += 1;
+        self.marks[next][back as usize] += 1;
+        self.came_from = Some(back);
+        self.revisited = self.visited[next];
+        self.visited[next] = true;
+        self.cell = next;
+        Some(next)
+    }
+}
+
+/// Dead-end filling.
+///
+/// Repeatedly fills in dead ends other than the start and goal until none
+/// are left. Yields every cell as it is filled; in a perfect maze the cells
+/// left open at the end are exactly the solution path.
+pub struct DeadEndFilling<'a> {
+    maze: &'a Maze,
+    from: usize,
+    to: usize,
+    filled: Vec<bool>,
+    pending: Vec<usize>,
+}
+
+impl<'a> DeadEndFilling<'a> {
+    pub fn new(maze: &'a Maze, from: usize, to: usize) -> Self {
+        let mut filling = DeadEndFilling {
+            maze,
+            from,
+            to,
+            filled: vec![false; maze.cell_count()],
+            pending: Vec::new(),
+        };
+        filling.pending = maze
+            .cells()
+            .rev()
+            .filter(|&cell| filling.is_dead_end(cell))
+            .collect();
+        filling
+    }
+
+    pub fn is_filled(&self, cell: usize) -> bool {
+        self.filled[cell]
+    }
+
+    /// Cells not filled in so far.
+    pub fn open_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.maze.cells().filter(|&cell| !self.filled[cell])
+    }
+
+    fn is_dead_end(&self, cell: usize) -> bool {
+        cell != self.from
+            && cell != self.to
+            && !self.filled[cell]
+            && self
+                .maze
+                .links(cell)
+                .filter(|&(_, n)| !self.filled[n])
+                .count()
+                <= 1
+    }
+}
+
+impl Iterator for DeadEndFilling<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some(cell) = self.pending.pop() {
+            if !self.is_dead_end(cell) {
+                continue;
+            }
+            self.filled[cell] = true;
+            for (_, next) in self.maze.links(cell) {
+                if self.is_dead_end(next) {
+                    self.pending.push(next);
+                }
+            }
+            return Some(cell);
+        }
+        None
+    }
+}
+
+/// The random mouse algorithm.
+///
+/// Follows corridors and picks a random way at junctions, only turning
+/// back at dead ends. Yields every cell it moves into until it
+/// stumbles on the goal; the same seed gives the same walk.
+pub struct RandomMouse<'a> {
+    maze: &'a Maze,
+    cell: usize,
+    came_from: Option<Direction>,
+    goal: usize,
+    rng: StdRng,
+}
+
+impl<'a> RandomMouse<'a> {
+    pub fn new(maze: &'a Maze, from: usize, to: usize, seed: u64) -> Self {
+        RandomMouse {
+            maze,
+            cell: from,
+            came_from: None,
+            goal: to,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+}
+
+impl Iterator for RandomMouse<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.cell == self.goal {
+            return None;
+        }
+        let ways = self
+            .maze
+            .links(self.cell)
+            .filter(|&(dir, _)| Some(dir) != self.came_from)
+            .collect::<Vec<_>>();
+        let (dir, next) = match ways.choose(&mut self.rng) {
+            Some(&way) => way,
+            None => {
+                let back = self.came_from?;
+                (back, self.maze.neighbor(self.cell, back)?)
+            }
+        };
+        self.came_from = self.maze.back(self.cell, dir);
+        self.cell = next;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{
+        braid::Braid,
+        floors::Floors,
+        generate::{self, MazeGenerator},
+        solve::{solve, Search},
+        Topology,
+    };
+
+    /// A perfect 3x3 maze. The path from the top-left start to the
+    /// bottom-right goal zigzags through the left two columns, the right
+    /// column above the goal is a dead end.
+    fn zigzag() -> Maze {
+        let mut maze = Maze::new(3, 3);
+        for (x, y, dir) in [
+            (0, 0, Direction::East),
+            (1, 0, Direction::South),
+            (1, 1, Direction::West),
+            (0, 1, Direction::South),
+            (0, 2, Direction::East),
+            (1, 2, Direction::East),
+            (2, 2, Direction::North),
+            (2, 1, Direction::North),
+        ] {
+            maze.carve(maze.cell(x, y), dir);
+        }
+        maze
+    }
+
+    /// Two floors of 3x2 cells. The way from the start at (0, 0) leads
+    /// east up the ramp at (1, 0) and round to the goal at (0, 1) upstairs;
+    /// the way south on the ground floor is a dead end.
+    fn upstairs() -> Maze {
+        let mut maze = Maze::with_floors(3, 2, 2);
+        for (floor, x, y, dir) in [
+            (0, 0, 0, Direction::East),
+            (0, 1, 0, Direction::Up),
+            (0, 0, 0, Direction::South),
+            (0, 0, 1, Direction::East),
+            (0, 1, 1, Direction::East),
+            (0, 2, 1, Direction::North),
+            (1, 1, 0, Direction::East),
+            (1, 2, 0, Direction::South),
+            (1, 2, 1, Direction::West),
+            (1, 1, 1, Direction::West),
+        ] {
+            maze.carve(maze.cell_on(floor, x, y), dir);
+        }
+        maze.set_goal(maze.cell_on(1, 0, 1));
+        maze
+    }
+
+    /// A loop around a free-standing pillar at (1, 1), with a corridor from
+    /// its east side to the goal at (4, 1). Starting at (1, 0), a wall
+    /// follower keeps its hand on the pillar and never leaves the loop.
+    fn pillar() -> Maze {
+        let mut maze = Maze::new(5, 3);
+        for (x, y, dir) in [
+            (0, 0, Direction::East),
+            (1, 0, Direction::East),
+            (2, 0, Direction::South),
+            (2, 1, Direction::South),
+            (2, 2, Direction::West),
+            (1, 2, Direction::West),
+            (0, 2, Direction::North),
+            (0, 1, Direction::North),
+            (2, 1, Direction::East),
+            (3, 1, Direction::East),
+        ] {
+            maze.carve(maze.cell(x, y), dir);
+        }
+        maze.set_start(maze.cell(1, 0));
+        maze.set_goal(maze.cell(4, 1));
+        maze
+    }
+
+    #[test]
+    fn wall_followers_solve_perfect_mazes() {
+        let maze = zigzag();
+        let shortest = solve(&maze, 0, 8, Search::Bfs).unwrap();
+        for hand in [Hand::Left, Hand::Right] {
+            let walk = WallFollower::new(&maze, 0, 8, hand).collect::<Vec<_>>();
+            assert_eq!(walk, shortest[1..]);
+        }
+
+        let maze = upstairs();
+        assert_eq!(maze.ramp(maze.cell(1, 0)), Some(Direction::East));
+        let shortest = solve(&maze, maze.start(), maze.goal(), Search::Bfs).unwrap();
+        for hand in [Hand::Left, Hand::Right] {
+            let walk = WallFollower::new(&maze, maze.start(), maze.goal(), hand);
+            assert_eq!(walk.collect::<Vec<_>>(), shortest[1..], "{hand:?}");
+        }
+        for seed in 0..5 {
+            let maze = Floors::default().generate(&generate::RecursiveBacktracker, 6, 5, seed);
+            for hand in [Hand::Left, Hand::Right] {
+                let walk = WallFollower::new(&maze, maze.start(), maze.goal(), hand);
+                assert_eq!(walk.last(), Some(maze.goal()));
+            }
+        }
+
+        for topology in Topology::ALL {
+            for seed in 0..5 {
+                let maze = generate::RecursiveBacktracker.generate_with(topology, 8, 10, seed);
+                for hand in [Hand::Left, Hand::Right] {
+                    let walk = WallFollower::new(&maze, maze.start(), maze.goal(), hand);
+                    assert_eq!(walk.last(), Some(maze.goal()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wall_followers_get_stuck_around_pillars() {
+        let maze = pillar();
+        for hand in [Hand::Left, Hand::Right] {
+            let mut walk = WallFollower::new(&maze, maze.start(), maze.goal(), hand);
+            assert!(walk.by_ref().all(|cell| cell != maze.goal()));
+            assert_ne!(walk.cell(), maze.goal());
+        }
+    }
+
+    #[test]
+    fn tremaux_finds_the_goal() {
+        let maze = pillar();
+        let mut walk = Tremaux::new(&maze, maze.start(), maze.goal());
+        assert_eq!(walk.by_ref().last(), Some(maze.goal()));
+        // Passages marked once lead from the start to the goal.
+        assert_eq!(walk.marks(maze.cell(3, 1), Direction::East), 1);
+        assert_eq!(walk.marks(maze.cell(2, 1), Direction::East), 1);
+
+        for topology in Topology::ALL {
+            for seed in 0..5 {
+                let mut maze = generate::Wilson.generate_with(topology, 8, 12, seed);
+                Braid {
+                    dead_end_removal: 0.5,
+                    extra_loops: 8,
+                }
+                .apply(&mut maze, seed);
+                let walk = Tremaux::new(&maze, maze.start(), maze.goal());
+                assert_eq!(walk.last(), Some(maze.goal()));
+            }
+        }
+    }
+
+    #[test]
+    fn tremaux_gives_up_when_the_goal_is_unreachable() {
+        let maze = pillar();
+        let walk = Tremaux::new(&maze, maze.start(), maze.cell(4, 0));
+        assert!(walk.count() < 100);
+    }
+
+    #[test]
+    fn dead_end_filling_leaves_the_solution() {
+        let maze = zigzag();
+        let mut filling = DeadEndFilling::new(&maze, 0, 8);
+        assert_eq!(filling.by_ref().collect::<Vec<_>>(), [2, 5]);
+        let open = filling.open_cells().collect::<Vec<_>>();
+        let mut shortest = solve(&maze, 0, 8, Search::Bfs).unwrap();
+        shortest.sort();
+        assert_eq!(open, shortest);
+
+        let maze = pillar();
+        let mut filling = DeadEndFilling::new(&maze, maze.start(), maze.goal());
+        filling.by_ref().for_each(drop);
+        for (x, y) in [(3, 0), (4, 0), (3, 2), (4, 2), (1, 1)] {
+            assert!(filling.is_filled(maze.cell(x, y)));
+        }
+        assert!(!filling.is_filled(maze.cell(3, 1)));
+        assert!(!filling.is_filled(maze.cell(0, 2)));
+    }
+
+    #[test]
+    fn random_mouse_eventually_finds_the_goal() {
+        for maze in [zigzag(), pillar()] {
+            for seed in 0..5 {
+                let walk = RandomMouse::new(&maze, maze.start(), maze.goal(), seed);
+                assert_eq!(walk.take(100_000).last(), Some(maze.goal()));
+            }
+        }
+        let maze = pillar();
+        let a = RandomMouse::new(&maze, maze.start(), maze.goal(), 3).collect::<Vec<_>>();
+        let b = RandomMouse::new(&maze, maze.start(), maze.goal(), 3).collect::<Vec<_>>();
+        assert_eq!(a, b);
+    }
+}
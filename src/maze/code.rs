@@ -0,0 +1,337 @@
+//! Compact binary encoding of mazes and the level codes built on it.
+//!
+//! The binary form is:
+//!
+//! | field   | encoding                                                  |
+//! |---------|-----------------------------------------------------------|
+//! | version | one byte, [`VERSION`]                                     |
+//! | width   | LEB128 varint                                             |
+//! | height  | LEB128 varint                                             |
+//! | start   | LEB128 varint cell index                                  |
+//! | goal    | LEB128 varint cell index                                  |
+//! | walls   | two bits per cell, row by row: east open, then south open |
+//! | crc     | CRC-32 of everything above, big-endian                    |
+//!
+//! Wall bits are packed from the lowest bit of each byte up, and the last
+//! byte is padded with zeros. A level code is the same bytes in Crockford
+//! base32, which avoids letters that are easily confused and reads back
+//! regardless of case or `-` separators. Only square mazes with a single
+//! floor and neither tunnels nor holes can be encoded, see
+//! [`Maze::check_plain`].
+
+use std::fmt;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{Direction, Maze, Unsupported};
+
+/// The newest encoding version; the only one [`Maze::decode`] reads.
+pub const VERSION: u8 = 1;
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Why a maze could not be decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A level code contains a character outside the base32 alphabet.
+    InvalidCharacter { position: usize, character: char },
+    /// The data ends early.
+    Truncated,
+    /// The data does not match its checksum.
+    Checksum,
+    /// The data was written by a newer or unknown version.
+    UnsupportedVersion(u8),
+    /// The checksum matches but the content makes no sense.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidCharacter {
+                position,
+                character,
+            } => write!(f, "invalid character {character:?} at position {position}"),
+            DecodeError::Truncated => write!(f, "the code is too short"),
+            DecodeError::Checksum => write!(f, "the code is damaged"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported maze version {v}"),
+            DecodeError::Invalid(reason) => write!(f, "invalid maze: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Maze {
+    pub fn encode(&self) -> Result<Bytes, Unsupported> {
+        self.check_plain()?;
+        let mut out = BytesMut::with_capacity(16 + self.cell_count() / 4);
+        out.put_u8(VERSION);
+        for value in [self.width(), self.height(), self.start(), self.goal()] {
+            put_varint(&mut out, value as u64);
+        }
+        let mut bits = 0u8;
+        for cell in self.cells() {
+            let shift = cell % 4 * 2;
+            if self.is_open(cell, Direction::East) {
+                bits |= 1 << shift;
+            }
+            if self.is_open(cell, Direction::South) {
+                bits |= 2 << shift;
+            }
+            if shift == 6 || cell + 1 == self.cell_count() {
+                out.put_u8(bits);
+                bits = 0;
+            }
+        }
+        let crc = crc32(&out);
+        out.put_u32(crc);
+        Ok(out.freeze())
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Maze, DecodeError> {
+        if data.len() < 5 {
+            return Err(DecodeError::Truncated);
+        }
+        let (mut data, mut crc) = data.split_at(data.len() - 4);
+        if crc32(data) != crc.get_u32() {
+            return Err(DecodeError::Checksum);
+        }
+        let version = data.get_u8();
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let width = get_varint(&mut data)?;
+        let height = get_varint(&mut data)?;
+        let (start, goal) = (get_varint(&mut data)?, get_varint(&mut data)?);
+        if width == 0 || height == 0 {
+            return Err(DecodeError::Invalid("the maze is empty"));
+        }
+        // Check the size against the data before allocating anything.
+        let cells = width
+            .checked_mul(height)
+            .filter(|&cells| cells / 4 + usize::from(cells % 4 != 0) == data.remaining())
+            .ok_or(DecodeError::Invalid("the size does not match the walls"))?;
+        if start >= cells || goal >= cells {
+            return Err(DecodeError::Invalid("start or goal is outside the maze"));
+        }
+
+        let mut maze = Maze::new(width, height);
+        let mut bits = 0;
+        for cell in maze.cells() {
+            if cell % 4 == 0 {
+                bits = data.get_u8();
+            }
+            for (bit, dir) in [(1, Direction::East), (2, Direction::South)] {
+                if bits & bit != 0 && !maze.carve(cell, dir) {
+                    return Err(DecodeError::Invalid("a passage leads out of the maze"));
+                }
+            }
+            bits >>= 2;
+        }
+        if bits != 0 {
+            return Err(DecodeError::Invalid("padding bits are set"));
+        }
+        maze.set_start(start);
+        maze.set_goal(goal);
+        Ok(maze)
+    }
+
+    /// The [encoding](Self::encode) as a shareable level code.
+    pub fn to_code(&self) -> Result<String, Unsupported> {
+        let data = self.encode()?;
+        let mut code = String::with_capacity(data.len() * 8 / 5 + 1);
+        let (mut buffer, mut bits) = (0u32, 0);
+        for &byte in data.iter() {
+            buffer = buffer << 8 | u32::from(byte);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                code.push(ALPHABET[(buffer >> bits & 31) as usize] as char);
+            }
+        }
+        if bits > 0 {
+            code.push(ALPHABET[(buffer << (5 - bits) & 31) as usize] as char);
+        }
+        Ok(code)
+    }
+
+    pub fn from_code(code: &str) -> Result<Maze, DecodeError> {
+        let mut data = Vec::with_capacity(code.len() * 5 / 8);
+        let (mut buffer, mut bits) = (0u32, 0);
+        for (position, character) in code.trim().chars().enumerate() {
+            let value = match character.to_ascii_uppercase() {
+                '-' => continue,
+                'O' => 0,
+                'I' | 'L' => 1,
+                c => ALPHABET.iter().position(|&a| a as char == c).ok_or(
+                    DecodeError::InvalidCharacter {
+                        position,
+                        character,
+                    },
+                )?,
+            };
+            buffer = buffer << 5 | value as u32;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                data.push((buffer >> bits) as u8);
+            }
+        }
+        // Leftover bits are padding; anything else means a damaged code.
+        if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+            return Err(DecodeError::Checksum);
+        }
+        Maze::decode(&data)
+    }
+}
+
+fn put_varint(out: &mut BytesMut, mut value: u64) {
+    while value >= 0x80 {
+        out.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.put_u8(value as u8);
+}
+
+fn get_varint(data: &mut &[u8]) -> Result<usize, DecodeError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        if !data.has_remaining() {
+            return Err(DecodeError::Truncated);
+        }
+        let byte = data.get_u8();
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return usize::try_from(value).map_err(|_| DecodeError::Invalid("number too large"));
+        }
+    }
+    Err(DecodeError::Invalid("number too large"))
+}
+
+/// CRC-32 as used by zlib and PNG.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                crc >> 1 ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generate;
+
+    #[test]
+    fn round_trips() {
+        for (width, height) in [(1, 1), (3, 1), (8, 8), (13, 7), (200, 3)] {
+            let mut maze = generate::recursive_backtracker(width, height, 9);
+            maze.set_start(maze.cell(width / 2, height - 1));
+            assert_eq!(Maze::decode(&maze.encode().unwrap()).unwrap(), maze);
+            assert_eq!(Maze::from_code(&maze.to_code().unwrap()).unwrap(), maze);
+        }
+    }
+
+    #[test]
+    fn codes_are_short_and_forgiving() {
+        let maze = generate::recursive_backtracker(8, 8, 1);
+        let code = maze.to_code().unwrap();
+        assert!(code.len() <= 45, "{code}");
+
+        let messy = code
+            .to_lowercase()
+            .replace('0', "o")
+            .replace('1', "l")
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| (i % 5 == 4).then_some('-').into_iter().chain([c]))
+            .collect::<String>();
+        assert_eq!(Maze::from_code(&messy).unwrap(), maze);
+    }
+
+    #[test]
+    fn corrupted_codes_fail_cleanly() {
+        let maze = generate::recursive_backtracker(6, 5, 3);
+        let code = maze.to_code().unwrap();
+
+        // Every single-character change is caught.
+        for i in 0..code.len() {
+            for replacement in ['0', 'Z', 'M'] {
+                let mut damaged = code.clone().into_bytes();
+                if damaged[i] == replacement as u8 {
+                    continue;
+                }
+                damaged[i] = replacement as u8;
+                let damaged = String::from_utf8(damaged).unwrap();
+                assert!(Maze::from_code(&damaged).is_err(), "{damaged}");
+            }
+        }
+        assert_eq!(
+            Maze::from_code(&code[..code.len() - 3]),
+            Err(DecodeError::Checksum)
+        );
+        assert_eq!(Maze::from_code("0000"), Err(DecodeError::Truncated));
+        assert_eq!(
+            Maze::from_code("12U4"),
+            Err(DecodeError::InvalidCharacter {
+                position: 2,
+                character: 'U'
+            })
+        );
+        assert!(Maze::from_code("").is_err());
+    }
+
+    #[test]
+    fn rejects_nonsense_with_a_valid_checksum() {
+        let with_crc = |body: &[u8]| {
+            let mut data = body.to_vec();
+            data.extend(crc32(body).to_be_bytes());
+            Maze::decode(&data)
+        };
+        assert_eq!(
+            with_crc(&[2, 1, 1, 0, 0, 0]),
+            Err(DecodeError::UnsupportedVersion(2))
+        );
+        assert!(matches!(
+            with_crc(&[1, 0, 1, 0, 0]),
+            Err(DecodeError::Invalid(_))
+        ));
+        // A 2x1 maze needs exactly one byte of walls.
+        assert!(matches!(
+            with_crc(&[1, 2, 1, 0, 1]),
+            Err(DecodeError::Invalid(_))
+        ));
+        assert!(matches!(
+            with_crc(&[1, 2, 1, 0, 1, 0, 0]),
+            Err(DecodeError::Invalid(_))
+        ));
+        // East passage out of the last column.
+        assert!(matches!(
+            with_crc(&[1, 2, 1, 0, 1, 0b0100]),
+            Err(DecodeError::Invalid(_))
+        ));
+        // A size that would not fit in memory.
+        let huge = [
+            1, 0xff, 0xff, 0xff, 0xff, 0x0f, 0xff, 0xff, 0xff, 0xff, 0x0f, 0, 0, 0,
+        ];
+        assert!(matches!(with_crc(&huge), Err(DecodeError::Invalid(_))));
+        assert_eq!(
+            with_crc(&[1, 2, 1, 0, 1, 0b0001]).unwrap().passage_count(),
+            1
+        );
+    }
+
+    #[test]
+    fn crc_matches_the_standard() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+}
@@ -0,0 +1,223 @@
+//! Mazes with several floors stacked on top of each other.
+//!
+//! Floors are joined by ramps. A passage up from a cell puts a ramp into
+//! it, rising across the cell from its only passage on its own floor to the
+//! opposite side, where the cell above has its only passage. The cell above
+//! has no floor of its own, so coming from its floor the ball rolls down
+//! the ramp. [`Maze::ramp`] tells which way a ramp rises.
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::{disjoint_set::DisjointSet, generate::MazeGenerator, Direction, Maze};
+
+const LEVEL: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+impl Maze {
+    /// The direction the ramp in `cell` rises towards, if `cell` has a
+    /// passage up laid out as described in the [module docs](self).
+    pub fn ramp(&self, cell: usize) -> Option<Direction> {
+        if !self.is_open(cell, Direction::Up) {
+            return None;
+        }
+        let above = self.neighbor(cell, Direction::Up)?;
+        let low = only_link(self, cell, Direction::Up).filter(|dir| !dir.is_vertical())?;
+        let high = only_link(self, above, Direction::Down)?;
+        (high == low.opposite()).then_some(high)
+    }
+}
+
+/// The only passage out of `cell` besides the one through `except`.
+fn only_link(maze: &Maze, cell: usize, except: Direction) -> Option<Direction> {
+    let mut links = maze
+        .links(cell)
+        .map(|(dir, _)| dir)
+        .filter(|&dir| dir != except);
+    let dir = links.next()?;
+    links.next().is_none().then_some(dir)
+}
+
+/// Settings for stacking mazes into several floors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Floors {
+    pub count: usize,
+    /// Ramps between every two neighboring floors. With one the maze stays
+    /// perfect; every further ramp adds a loop.
+    pub ramps: usize,
+}
+
+impl Default for Floors {
+    fn default() -> Self {
+        Floors { count: 3, ramps: 1 }
+    }
+}
+
+impl Floors {
+    /// Lays out every floor with `generator`, then joins neighboring floors
+    /// with ramps. The same seed always gives the same layout.
+    ///
+    /// Panics if the floors are too small to make room for the ramps;
+    /// floors of at least 3 by 2 cells fit one.
+    pub fn generate(
+        &self,
+        generator: &dyn MazeGenerator,
+        width: usize,
+        height: usize,
+        seed: u64,
+    ) -> Maze {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Maze::with_floors(width, height, self.count);
+        for floor in 0..self.count {
+            let mut plan = Maze::new(width, height);
+            generator.carve(&mut plan, &mut rng);
+            for cell in plan.cells() {
+                let (x, y) = plan.coords(cell);
+                for (dir, _) in plan.links(cell) {
+                    maze.carve(maze.cell_on(floor, x, y), dir);
+                }
+            }
+        }
+
+        for floor in 0..self.count - 1 {
+            for _ in 0..self.ramps {
+                let placed = (0..64 * width * height).any(|_| add_ramp(&mut maze, floor, &mut rng));
+                assert!(
+                    placed,
+                    "no room for a ramp between floors {floor} and {}",
+                    floor + 1
+                );
+            }
+        }
+        maze
+    }
+}
+
+/// Tries to put a ramp into a random cell of `floor`, rising to the floor
+/// above. Leaves the maze untouched and returns `false` if that cell does
+/// not work out.
+fn add_ramp(maze: &mut Maze, floor: usize, rng: &mut impl Rng) -> bool {
+    let (x, y) = (
+        rng.gen_range(0..maze.width()),
+        rng.gen_range(0..maze.height()),
+    );
+    let rises = *LEVEL.choose(rng).expect("there are directions");
+    let (low, high) = (maze.cell_on(floor, x, y), maze.cell_on(floor + 1, x, y));
+
+    let mut candidate = maze.clone();
+    if !make_dead_end(&mut candidate, low, rises.opposite(), rng)
+        || !make_dead_end(&mut candidate, high, rises, rng)
+    {
+        return false;
+    }
+    candidate.carve(low, Direction::Up);
+    *maze = candidate;
+    true
+}
+
+/// Rearranges the passages of the floor `cell` is on so that its only one
+/// leads `dir`, keeping the rest of the floor connected. Cells next to a
+/// ramp keep their passages. Returns `false` if that is not possible.
+fn make_dead_end(maze: &mut Maze, cell: usize, dir: Direction, rng: &mut impl Rng) -> bool {
+    let has_ramp =
+        |maze: &Maze, c: usize| maze.is_open(c, Direction::Up) || maze.is_open(c, Direction::Down);
+    let Some(exit) = maze.neighbor(cell, dir) else {
+        return false;
+    };
+    if has_ramp(maze, cell) || has_ramp(maze, exit) {
+        return false;
+    }
+
+    for side in LEVEL {
+        maze.build_wall(cell, side);
+    }
+    let per_floor = maze.width() * maze.height();
+    let first = maze.floor(cell) * per_floor;
+    let mut sets = DisjointSet::new(maze.cell_count());
+    for c in first..first + per_floor {
+        for (side, n) in maze.links(c) {
+            if !side.is_vertical() {
+                sets.union(c, n);
+            }
+        }
+    }
+    // Join the pieces the floor fell into around `cell` at random spots.
+    let mut walls = (first..first + per_floor)
+        .flat_map(|c| [(c, Direction::East), (c, Direction::South)])
+        .filter_map(|(c, side)| Some((c, side, maze.neighbor(c, side)?)))
+        .filter(|&(c, side, n)| {
+            maze.has_wall(c, side) && ![c, n].iter().any(|&c| c == cell || has_ramp(maze, c))
+        })
+        .collect::<Vec<_>>();
+    walls.shuffle(rng);
+    for (c, side, n) in walls {
+        if sets.union(c, n) {
+            maze.carve(c, side);
+        }
+    }
+    maze.carve(cell, dir);
+    sets.union(cell, exit);
+
+    let root = sets.find(cell);
+    (first..first + per_floor).all(|c| sets.find(c) == root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{
+        generate,
+        solve::{distances, solve, Search},
+    };
+
+    #[test]
+    fn floors_are_perfect_and_joined_by_ramps() {
+        for name in generate::NAMES {
+            let generator = generate::by_name(name).unwrap();
+            for seed in 0..4 {
+                let maze = Floors::default().generate(generator.as_ref(), 6, 5, seed);
+                assert_eq!(maze.floors(), 3);
+                assert!(distances(&maze, 0).iter().all(Option::is_some), "{name}");
+                assert_eq!(maze.passage_count(), maze.cell_count() - 1, "{name}");
+
+                let ramps = maze
+                    .cells()
+                    .filter(|&c| maze.is_open(c, Direction::Up))
+                    .collect::<Vec<_>>();
+                assert_eq!(ramps.len(), 2);
+                assert!(ramps.iter().all(|&c| maze.ramp(c).is_some()), "{name}");
+                assert_eq!(
+                    Floors::default().generate(generator.as_ref(), 6, 5, seed),
+                    maze
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn more_ramps_add_loops() {
+        let floors = Floors { count: 4, ramps: 3 };
+        let maze = floors.generate(&generate::Prim, 8, 8, 7);
+        let ramps = maze.cells().filter_map(|c| maze.ramp(c)).count();
+        assert_eq!(ramps, 9);
+        assert_eq!(maze.passage_count(), maze.cell_count() - 1 + 6);
+        let path = solve(&maze, maze.start(), maze.goal(), Search::AStar).unwrap();
+        assert_eq!(maze.floor(*path.last().unwrap()), 3);
+    }
+
+    #[test]
+    fn ramps_need_a_dead_end_at_both_ends() {
+        let mut maze = Maze::with_floors(3, 1, 2);
+        let (low, high) = (maze.cell(1, 0), maze.cell_on(1, 1, 0));
+        maze.carve(low, Direction::Up);
+        maze.carve(low, Direction::West);
+        assert_eq!(maze.ramp(low), None);
+        maze.carve(high, Direction::East);
+        assert_eq!(maze.ramp(low), Some(Direction::East));
+        maze.carve(high, Direction::West);
+        assert_eq!(maze.ramp(low), None);
+    }
+}
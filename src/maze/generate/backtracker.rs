@@ -0,0 +1,39 @@
+use rand::{seq::SliceRandom, RngCore};
+
+use super::MazeGenerator;
+use crate::maze::Maze;
+
+/// Randomized depth-first search.
+///
+/// Produces long, winding corridors with few dead ends.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RecursiveBacktracker;
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn name(&self) -> &'static str {
+        "backtracker"
+    }
+
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut visited = vec![false; maze.cell_count()];
+        let mut stack = vec![maze.start()];
+        visited[maze.start()] = true;
+
+        while let Some(&cell) = stack.last() {
+            let unvisited = maze
+                .neighbors(cell)
+                .filter(|&(_, n)| !visited[n])
+                .collect::<Vec<_>>();
+            match unvisited.choose(rng) {
+                Some(&(dir, next)) => {
+                    maze.carve(cell, dir);
+                    visited[next] = true;
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
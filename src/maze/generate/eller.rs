@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::MazeGenerator;
+use crate::maze::{disjoint_set::DisjointSet, Direction, Maze};
+
+/// Eller's algorithm.
+///
+/// Works one row at a time, tracking which cells of the current row are
+/// already connected. Tends towards long horizontal corridors. In hex
+/// mazes the rows zigzag and the diagonals between rows stay walled; in
+/// polar mazes the rows are the rings, from the center outwards. Holes in
+/// masked mazes can cut a set off from the rows below; such sets are
+/// joined up wherever they touch at the end.
+#[derive(Clone, Copy, Debug)]
+pub struct Eller {
+    /// Chance of joining two horizontally adjacent, unconnected cells.
+    pub join_chance: f64,
+    /// Chance of each extra vertical passage beyond the one every set
+    /// needs.
+    pub drop_chance: f64,
+}
+
+impl Default for Eller {
+    fn default() -> Self {
+        Eller {
+            join_chance: 0.5,
+            drop_chance: 0.3,
+        }
+    }
+}
+
+impl MazeGenerator for Eller {
+    fn name(&self) -> &'static str {
+        "eller"
+    }
+
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let height = maze.height();
+        // Sets are kept over the whole maze; only the current row's cells
+        // are ever merged, so this is equivalent to the usual per-row
+        // bookkeeping.
+        let mut sets = DisjointSet::new(maze.cell_count());
+
+        for y in 0..height {
+            let last_row = y + 1 == height;
+            for x in 0..maze.row_len(y) - 1 {
+                let (a, b) = (maze.cell(x, y), maze.cell(x + 1, y));
+                // Cells in a row are adjacent unless one is a hole.
+                let Some(dir) = maze.direction_to(a, b) else {
+                    continue;
+                };
+                if sets.find(a) != sets.find(b) && (last_row || rng.gen_bool(self.join_chance)) {
+                    sets.union(a, b);
+                    maze.carve(a, dir);
+                }
+            }
+            if last_row {
+                break;
+            }
+
+            let mut groups = BTreeMap::<usize, Vec<usize>>::new();
+            for x in 0..maze.row_len(y) {
+                let cell = maze.cell(x, y);
+                if maze.neighbor(cell, Direction::South).is_none() {
+                    continue;
+                }
+                groups.entry(sets.find(cell)).or_default().push(cell);
+            }
+            for mut group in groups.into_values() {
+                group.shuffle(rng);
+                for (i, &cell) in group.iter().enumerate() {
+                    if i == 0 || rng.gen_bool(self.drop_chance) {
+                        let below = maze
+                            .neighbor(cell, Direction::South)
+                            .expect("only cells with one below are grouped");
+                        sets.union(cell, below);
+                        maze.carve(cell, Direction::South);
+                    }
+                }
+            }
+        }
+
+        for cell in maze.cells() {
+            for (dir, other) in maze.neighbors(cell).collect::<Vec<_>>() {
+                if sets.union(cell, other) {
+                    maze.carve(cell, dir);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use rand::{Rng, RngCore};
+
+use super::MazeGenerator;
+use crate::maze::Maze;
+
+/// Which active cell the growing-tree algorithm extends next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Selection {
+    /// Always the most recently added cell; behaves like the backtracker.
+    Newest,
+    /// Always the oldest cell, giving straight corridors from the start.
+    Oldest,
+    /// A random cell; behaves much like Prim's algorithm.
+    Random,
+    /// The newest cell with the given probability, otherwise a random one.
+    Mixed(f64),
+}
+
+impl Selection {
+    /// Parses `newest`, `oldest`, `random` or `mixed`, optionally followed by
+    /// `=<probability>` for the latter.
+    pub fn from_name(name: &str) -> Option<Selection> {
+        match name {
+            "newest" => Some(Selection::Newest),
+            "oldest" => Some(Selection::Oldest),
+            "random" => Some(Selection::Random),
+            "mixed" => Some(Selection::Mixed(0.5)),
+            _ => {
+                let p = name.strip_prefix("mixed=")?.parse::<f64>().ok()?;
+                (0.0..=1.0).contains(&p).then_some(Selection::Mixed(p))
+            }
+        }
+    }
+
+    fn pick(self, len: usize, rng: &mut dyn RngCore) -> usize {
+        match self {
+            Selection::Newest => len - 1,
+            Selection::Oldest => 0,
+            Selection::Random => rng.gen_range(0..len),
+            Selection::Mixed(p) => {
+                if rng.gen_bool(p) {
+                    len - 1
+                } else {
+                    rng.gen_range(0..len)
+                }
+            }
+        }
+    }
+}
+
+/// The growing-tree algorithm.
+///
+/// Keeps a list of active cells and repeatedly extends one of them; the
+/// [`Selection`] policy decides which, and with it the maze's texture.
+#[derive(Clone, Copy, Debug)]
+pub struct GrowingTree {
+    pub selection: Selection,
+}
+
+impl Default for GrowingTree {
+    fn default() -> Self {
+        GrowingTree {
+            selection: Selection::Mixed(0.5),
+        }
+    }
+}
+
+impl MazeGenerator for GrowingTree {
+    fn name(&self) -> &'static str {
+        "growing-tree"
+    }
+
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut visited = vec![false; maze.cell_count()];
+        let mut active = vec![maze.start()];
+        visited[maze.start()] = true;
+
+        while !active.is_empty() {
+            let index = self.selection.pick(active.len(), rng);
+            let cell = active[index];
+            let unvisited = maze
+                .neighbors(cell)
+                .filter(|&(_, n)| !visited[n])
+                .collect::<Vec<_>>();
+            if unvisited.is_empty() {
+                active.remove(index);
+                continue;
+            }
+            let (dir, next) = unvisited[rng.gen_range(0..unvisited.len())];
+            maze.carve(cell, dir);
+            visited[next] = true;
+            active.push(next);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use rand::{seq::SliceRandom, RngCore};
+
+use super::MazeGenerator;
+use crate::maze::{disjoint_set::DisjointSet, Maze};
+
+/// Randomized Kruskal's algorithm.
+///
+/// Knocks down walls in random order whenever they separate two regions
+/// that are not connected yet. The result has lots of short dead ends.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kruskal;
+
+impl MazeGenerator for Kruskal {
+    fn name(&self) -> &'static str {
+        "kruskal"
+    }
+
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let grid = &*maze;
+        // Every inner wall once, from the cell with the lower index.
+        let mut walls = grid
+            .cells()
+            .flat_map(|cell| {
+                grid.neighbors(cell)
+                    .filter(move |&(_, other)| other > cell)
+                    .map(move |(dir, _)| (cell, dir))
+            })
+            .collect::<Vec<_>>();
+        walls.shuffle(rng);
+
+        let mut sets = DisjointSet::new(maze.cell_count());
+        for (cell, dir) in walls {
+            let Some(other) = maze.neighbor(cell, dir) else {
+                continue;
+            };
+            if sets.union(cell, other) {
+                maze.carve(cell, dir);
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Perfect maze generators.
+//!
+//! Every generator carves a spanning tree into a fully walled maze, so the
+//! result has exactly one path between any two cells. They differ in the
+//! "texture" of the layout: corridor length, dead-end count and bias.
+//! [`extend`] grows a finished maze by rows carved with any of them.
+
+mod backtracker;
+mod eller;
+mod growing_tree;
+mod kruskal;
+mod prim;
+mod wilson;
+
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+use super::{Direction, Maze, Topology};
+
+pub use backtracker::RecursiveBacktracker;
+pub use eller::Eller;
+pub use growing_tree::{GrowingTree, Selection};
+pub use kruskal::Kruskal;
+pub use prim::Prim;
+pub use wilson::Wilson;
+
+/// An algorithm that turns a fully walled maze into a perfect one.
+pub trait MazeGenerator {
+    /// The name [`by_name`] knows this generator under.
+    fn name(&self) -> &'static str;
+
+    /// Carves passages into `maze`, which must not have any yet. The cells
+    /// of a masked maze must all be connected, see [`mask`](super::mask).
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore);
+
+    /// Generates a square `width` by `height` maze. The same seed always
+    /// gives the same layout.
+    fn generate(&self, width: usize, height: usize, seed: u64) -> Maze {
+        self.generate_with(Topology::Square, width, height, seed)
+    }
+
+    /// Like [`generate`](Self::generate) with cells of any shape.
+    fn generate_with(&self, topology: Topology, width: usize, height: usize, seed: u64) -> Maze {
+        let mut maze = Maze::with_topology(topology, width, height);
+        self.carve(&mut maze, &mut StdRng::seed_from_u64(seed));
+        maze
+    }
+}
+
+/// Names accepted by [`by_name`].
+pub const NAMES: [&str; 6] = [
+    "backtracker",
+    "prim",
+    "kruskal",
+    "wilson",
+    "eller",
+    "growing-tree",
+];
+
+/// Looks up a generator by name.
+///
+/// The growing-tree generator takes an optional selection policy after a
+/// colon, as in `growing-tree:oldest` or `growing-tree:mixed=0.25`.
+pub fn by_name(name: &str) -> Option<Box<dyn MazeGenerator>> {
+    let (name, selection) = match name.split_once(':') {
+        Some((name, selection)) => (name, Some(selection)),
+        None => (name, None),
+    };
+    let generator: Box<dyn MazeGenerator> = match name {
+        "growing-tree" => Box::new(match selection {
+            Some(selection) => GrowingTree {
+                selection: Selection::from_name(selection)?,
+            },
+            None => GrowingTree::default(),
+        }),
+        _ if selection.is_some() => return None,
+        "backtracker" => Box::new(RecursiveBacktracker),
+        "prim" => Box::new(Prim),
+        "kruskal" => Box::new(Kruskal),
+        "wilson" => Box::new(Wilson),
+        "eller" => Box::new(Eller::default()),
+        _ => return None,
+    };
+    Some(generator)
+}
+
+/// Generates a perfect maze with the recursive backtracker (randomized
+/// depth-first search) algorithm.
+///
+/// The layout only depends on the arguments, so a seed is enough to
+/// reproduce or share a maze.
+pub fn recursive_backtracker(width: usize, height: usize, seed: u64) -> Maze {
+    RecursiveBacktracker.generate(width, height, seed)
+}
+
+/// Grows `maze` by `rows` rows along its south edge and carves them with
+/// `generator`, joined to the old last row by a single passage. That
+/// passage is the only change to the cells already there, so a perfect
+/// maze stays perfect and every new cell can be reached from every old
+/// one. Returns the cell of the old last row the passage leads south out
+/// of.
+///
+/// Panics for the mazes [`Maze::add_rows`] refuses and for masked mazes,
+/// whose last row might not have a cell to join from.
+pub fn extend(
+    maze: &mut Maze,
+    generator: &dyn MazeGenerator,
+    rows: usize,
+    rng: &mut dyn RngCore,
+) -> usize {
+    assert_eq!(maze.hole_count(), 0, "masked mazes cannot grow");
+    let (width, first) = (maze.width(), maze.height());
+    let mut block = Maze::with_topology(maze.topology(), width, rows);
+    generator.carve(&mut block, rng);
+    maze.add_rows(rows);
+    for cell in block.cells() {
+        let (x, y) = block.coords(cell);
+        for (dir, _) in block.links(cell) {
+            maze.carve(maze.cell(x, first + y), dir);
+        }
+    }
+    let exit = maze.cell(rng.gen_range(0..width), first - 1);
+    maze.carve(exit, Direction::South);
+    exit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that every cell is reachable from the start and that there is
+    /// exactly one path between any two cells.
+    fn assert_perfect(maze: &Maze) {
+        let mut seen = vec![false; maze.cell_count()];
+        let mut stack = vec![maze.start()];
+        seen[maze.start()] = true;
+        while let Some(cell) = stack.pop() {
+            for (_, next) in maze.links(cell) {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        assert!(seen.iter().all(|&s| s), "some cells are unreachable");
+        // A connected graph is a tree exactly when it has one edge less than
+        // it has nodes.
+        assert_eq!(
+            maze.passage_count(),
+            maze.cell_count() - 1,
+            "maze has loops"
+        );
+    }
+
+    fn assert_generator_is_perfect(generator: &dyn MazeGenerator) {
+        for topology in Topology::ALL {
+            // Polar mazes have 3 to 8 cells around the center.
+            let sizes: &[(usize, usize)] = match topology {
+                Topology::Polar => &[(3, 1), (3, 2), (8, 2), (6, 9), (5, 25)],
+                _ => &[(1, 1), (1, 9), (9, 1), (2, 2), (12, 7), (25, 25)],
+            };
+            for &(width, height) in sizes {
+                for seed in 0..16 {
+                    let maze = generator.generate_with(topology, width, height, seed);
+                    assert_perfect(&maze);
+                    assert_eq!(maze, generator.generate_with(topology, width, height, seed));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn backtracker_makes_perfect_mazes() {
+        for seed in 0..20 {
+            assert_perfect(&recursive_backtracker(12, 7, seed));
+        }
+        assert_perfect(&recursive_backtracker(1, 1, 0));
+        assert_perfect(&recursive_backtracker(30, 1, 3));
+    }
+
+    #[test]
+    fn same_seed_same_maze() {
+        assert_eq!(
+            recursive_backtracker(16, 16, 1234),
+            recursive_backtracker(16, 16, 1234)
+        );
+        assert_ne!(
+            recursive_backtracker(16, 16, 1234),
+            recursive_backtracker(16, 16, 1235)
+        );
+    }
+
+    #[test]
+    fn every_named_generator_makes_perfect_mazes() {
+        for name in NAMES {
+            let generator = by_name(name).unwrap();
+            assert_eq!(generator.name(), name);
+            assert_generator_is_perfect(generator.as_ref());
+        }
+    }
+
+    #[test]
+    fn every_growing_tree_selection_makes_perfect_mazes() {
+        for selection in ["newest", "oldest", "random", "mixed", "mixed=0.9"] {
+            let generator = by_name(&format!("growing-tree:{selection}")).unwrap();
+            assert_generator_is_perfect(generator.as_ref());
+        }
+    }
+
+    #[test]
+    fn extending_keeps_the_cells_already_there() {
+        for name in NAMES {
+            let generator = by_name(name).unwrap();
+            for topology in [Topology::Square, Topology::Hex] {
+                let mut rng = StdRng::seed_from_u64(5);
+                let mut maze = generator.generate_with(topology, 7, 3, 5);
+                for rows in [1, 4, 2] {
+                    let old = maze.clone();
+                    let exit = extend(&mut maze, generator.as_ref(), rows, &mut rng);
+                    assert_eq!(maze.height(), old.height() + rows);
+                    assert_eq!(maze.coords(exit).1, old.height() - 1);
+                    for cell in old.cells().filter(|&cell| cell != exit) {
+                        assert_eq!(maze.wall_mask(cell), old.wall_mask(cell), "{cell}");
+                    }
+                    assert_eq!(maze.links(exit).count(), old.links(exit).count() + 1);
+                    assert_perfect(&maze);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert!(by_name("aldous-broder").is_none());
+        assert!(by_name("prim:newest").is_none());
+        assert!(by_name("growing-tree:sideways").is_none());
+        assert!(by_name("growing-tree:mixed=2").is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::MazeGenerator;
+use crate::maze::Maze;
+
+/// Randomized Prim's algorithm.
+///
+/// Grows the maze outwards from the start, which gives many short dead
+/// ends radiating from it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn name(&self) -> &'static str {
+        "prim"
+    }
+
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut in_maze = vec![false; maze.cell_count()];
+        let mut queued = vec![false; maze.cell_count()];
+        let mut frontier = vec![maze.start()];
+        queued[maze.start()] = true;
+
+        while !frontier.is_empty() {
+            let cell = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            let inside = maze
+                .neighbors(cell)
+                .filter(|&(_, n)| in_maze[n])
+                .collect::<Vec<_>>();
+            if let Some(&(dir, _)) = inside.choose(rng) {
+                maze.carve(cell, dir);
+            }
+            in_maze[cell] = true;
+            for (_, n) in maze.neighbors(cell) {
+                if !queued[n] {
+                    queued[n] = true;
+                    frontier.push(n);
+                }
+            }
+        }
+    }
+}
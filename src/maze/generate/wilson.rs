@@ -0,0 +1,53 @@
+use rand::{seq::IteratorRandom, Rng, RngCore};
+
+use super::MazeGenerator;
+use crate::maze::{Direction, Maze};
+
+/// Wilson's algorithm, built from loop-erased random walks.
+///
+/// Samples uniformly from all possible perfect mazes, so it has no bias
+/// towards any particular texture.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn name(&self) -> &'static str {
+        "wilson"
+    }
+
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let cells = maze
+            .cells()
+            .filter(|&cell| maze.is_enabled(cell))
+            .collect::<Vec<_>>();
+        let mut in_maze = vec![false; maze.cell_count()];
+        in_maze[cells[rng.gen_range(0..cells.len())]] = true;
+        // The direction the walk last left each cell in. Overwriting it when
+        // the walk comes back erases the loop it just made.
+        let mut exit = vec![Direction::North; maze.cell_count()];
+
+        for first in cells {
+            if in_maze[first] {
+                continue;
+            }
+            let mut cell = first;
+            while !in_maze[cell] {
+                let (dir, next) = maze
+                    .neighbors(cell)
+                    .choose(rng)
+                    .expect("the cells of the maze are connected");
+                exit[cell] = dir;
+                cell = next;
+            }
+
+            let mut cell = first;
+            while !in_maze[cell] {
+                in_maze[cell] = true;
+                maze.carve(cell, exit[cell]);
+                cell = maze
+                    .neighbor(cell, exit[cell])
+                    .expect("walk stayed inside the maze");
+            }
+        }
+    }
+}
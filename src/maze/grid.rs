@@ -0,0 +1,834 @@
+use std::{collections::BTreeMap, fmt};
+
+use super::{mask::Mask, Topology};
+
+/// A side of a cell.
+///
+/// North points towards row 0, west towards column 0. Square cells use the
+/// four cardinal directions, hex cells north, south and the diagonals; see
+/// [`Topology::directions`]. Polar mazes give them their own meaning, see
+/// [`Topology::Polar`]. Up and down lead to the floors above and below in
+/// mazes with several floors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+    NorthEast,
+    SouthEast,
+    SouthWest,
+    NorthWest,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 10] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
+
+    /// The direction a quarter turn counter-clockwise, seen from above.
+    /// Up and down stay as they are.
+    pub fn left(self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+            Direction::NorthEast => Direction::NorthWest,
+            Direction::SouthEast => Direction::NorthEast,
+            Direction::SouthWest => Direction::SouthEast,
+            Direction::NorthWest => Direction::SouthWest,
+            Direction::Up | Direction::Down => self,
+        }
+    }
+
+    /// The direction a quarter turn clockwise, seen from above.
+    pub fn right(self) -> Direction {
+        match self {
+            Direction::Up | Direction::Down => self,
+            _ => self.left().opposite(),
+        }
+    }
+
+    /// Column/row offset of the neighbor on this side in a square grid.
+    /// Hex grids shift diagonals by column, see [`Topology::step`].
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, -1),
+            Direction::SouthEast => (1, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::NorthWest => (-1, -1),
+            Direction::Up | Direction::Down => (0, 0),
+        }
+    }
+
+    /// The bit of this side in [`Maze::wall_mask`].
+    pub fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// A maze of `width` by `height` cells, on one or more floors.
+///
+/// Cells are addressed by their index `y * width + x`, or ring by ring in
+/// polar mazes. Further floors follow the first one, so the cell above has
+/// an index `width * height` higher. Tunnels of weave mazes come last, see
+/// [`Maze::add_tunnel`]. Masked mazes leave holes in the grid, see
+/// [`Maze::with_mask`]. Every cell stores a bitmask of the sides it has a
+/// passage through; a side without a passage is a wall. Both cells of a
+/// passage are always kept in sync.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Maze {
+    topology: Topology,
+    width: usize,
+    height: usize,
+    floors: usize,
+    cells: Vec<u16>,
+    /// Index of the first cell of every ring, then the cell count. Empty
+    /// unless the maze is polar.
+    rings: Vec<usize>,
+    /// Whether each cell of the grid is left out of the maze. Empty unless
+    /// the maze is masked.
+    holes: Vec<bool>,
+    /// The crossing every tunnel runs under and the way it runs, east or
+    /// south, in the order of the tunnel cells.
+    tunnels: Vec<(usize, Direction)>,
+    /// The tunnel cell under every crossing.
+    crossings: BTreeMap<usize, usize>,
+    start: usize,
+    goal: usize,
+}
+
+impl Maze {
+    /// Creates a square maze where every cell is walled in on all four
+    /// sides.
+    ///
+    /// The start is the top-left cell and the goal the bottom-right one.
+    pub fn new(width: usize, height: usize) -> Self {
+        Maze::with_topology(Topology::Square, width, height)
+    }
+
+    /// Like [`Maze::new`] with cells of any shape.
+    ///
+    /// Polar mazes start on the outer ring and have their goal at the
+    /// center.
+    pub fn with_topology(topology: Topology, width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "maze must have at least one cell");
+        if topology != Topology::Polar {
+            return Self {
+                topology,
+                width,
+                height,
+                floors: 1,
+                cells: vec![0; width * height],
+                rings: Vec::new(),
+                holes: Vec::new(),
+                tunnels: Vec::new(),
+                crossings: BTreeMap::new(),
+                start: 0,
+                goal: width * height - 1,
+            };
+        }
+
+        assert!(
+            (3..=8).contains(&width),
+            "polar mazes need 3 to 8 cells around the center"
+        );
+        let mut rings = vec![0];
+        for len in ring_lens(width).take(height) {
+            rings.push(rings[rings.len() - 1] + len);
+        }
+        Self {
+            topology,
+            width,
+            height,
+            floors: 1,
+            cells: vec![0; rings[height]],
+            start: rings[height - 1],
+            goal: 0,
+            rings,
+            holes: Vec::new(),
+            tunnels: Vec::new(),
+            crossings: BTreeMap::new(),
+        }
+    }
+
+    /// The number of cells [`Maze::with_topology`] or, with several
+    /// `floors`, [`Maze::with_floors`] would make, without making them.
+    /// `None` if that is more than `limit`, so that sizes from untrusted
+    /// input can be checked before allocating anything.
+    pub fn cell_count_for(
+        topology: Topology,
+        width: usize,
+        height: usize,
+        floors: usize,
+        limit: usize,
+    ) -> Option<usize> {
+        if topology != Topology::Polar {
+            return width
+                .checked_mul(height)?
+                .checked_mul(floors)
+                .filter(|&cells| cells <= limit);
+        }
+        // Every ring has a cell at least, so this stops after `limit` rings.
+        ring_lens(width)
+            .take(height)
+            .try_fold(0usize, |cells, len| {
+                cells.checked_add(len).filter(|&cells| cells <= limit)
+            })
+    }
+
+    /// A square maze of `floors` stacked `width` by `height` floors.
+    ///
+    /// The start is the top-left cell of the ground floor and the goal the
+    /// bottom-right cell of the top floor.
+    pub fn with_floors(width: usize, height: usize, floors: usize) -> Self {
+        assert!(floors > 0, "maze must have at least one floor");
+        let cells = width * height * floors;
+        Self {
+            floors,
+            cells: vec![0; cells],
+            goal: cells - 1,
+            ..Maze::new(width, height)
+        }
+    }
+
+    /// A maze of the cells `mask` enables, on a grid of the mask's size.
+    /// The cells it leaves out are holes without any sides, so nothing
+    /// leads into them.
+    ///
+    /// The start is the first enabled cell and the goal the last one.
+    /// Panics for polar mazes, whose rings do not line up with a grid, and
+    /// if the mask enables no cells.
+    pub fn with_mask(topology: Topology, mask: &Mask) -> Self {
+        assert_ne!(topology, Topology::Polar, "polar mazes cannot be masked");
+        let holes = (0..mask.height())
+            .flat_map(|y| (0..mask.width()).map(move |x| !mask.is_enabled(x, y)))
+            .collect::<Vec<_>>();
+        let start = holes
+            .iter()
+            .position(|&hole| !hole)
+            .expect("a mask needs at least one cell");
+        let goal = holes.iter().rposition(|&hole| !hole).unwrap_or(start);
+        Self {
+            holes: if holes.contains(&true) {
+                holes
+            } else {
+                Vec::new()
+            },
+            start,
+            goal,
+            ..Maze::with_topology(topology, mask.width(), mask.height())
+        }
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn floors(&self) -> usize {
+        self.floors
+    }
+
+    /// The floor `cell` is on, counting from 0 at the bottom.
+    pub fn floor(&self, cell: usize) -> usize {
+        match self.floors {
+            1 => 0,
+            _ => cell / (self.width * self.height),
+        }
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cells(&self) -> std::ops::Range<usize> {
+        0..self.cells.len()
+    }
+
+    /// Number of cells in row `y`, which is the width except in polar
+    /// mazes.
+    pub fn row_len(&self, y: usize) -> usize {
+        match self.topology {
+            Topology::Polar => self.rings[y + 1] - self.rings[y],
+            _ => self.width,
+        }
+    }
+
+    /// Index of the cell at column `x`, row `y` on the ground floor.
+    pub fn cell(&self, x: usize, y: usize) -> usize {
+        debug_assert!(y < self.height && x < self.row_len(y));
+        match self.topology {
+            Topology::Polar => self.rings[y] + x,
+            _ => y * self.width + x,
+        }
+    }
+
+    /// Index of the cell at column `x`, row `y` on floor `floor`.
+    pub fn cell_on(&self, floor: usize, x: usize, y: usize) -> usize {
+        debug_assert!(floor < self.floors);
+        floor * self.width * self.height + self.cell(x, y)
+    }
+
+    /// Column and row of `cell` on its floor. A tunnel has those of the
+    /// crossing above it.
+    pub fn coords(&self, cell: usize) -> (usize, usize) {
+        if let Some((crossing, _)) = self.tunnel(cell) {
+            return self.coords(crossing);
+        }
+        match self.topology {
+            Topology::Polar => {
+                let ring = self.rings.partition_point(|&first| first <= cell) - 1;
+                (cell - self.rings[ring], ring)
+            }
+            _ => (cell % self.width, cell / self.width % self.height),
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn set_start(&mut self, cell: usize) {
+        assert!(cell < self.cells.len());
+        self.start = cell;
+    }
+
+    pub fn goal(&self) -> usize {
+        self.goal
+    }
+
+    pub fn set_goal(&mut self, cell: usize) {
+        assert!(cell < self.cells.len());
+        self.goal = cell;
+    }
+
+    /// The sides of `cell`, clockwise seen from above, then up and down
+    /// where there are floors above and below. The same for every cell of
+    /// a single floor except in polar mazes and at tunnels. Holes have
+    /// none.
+    pub fn sides(&self, cell: usize) -> impl Iterator<Item = Direction> {
+        use Direction::*;
+        let floor = self.floor(cell);
+        let sides: &'static [Direction] = match self.topology {
+            _ if !self.is_enabled(cell) => &[],
+            _ if self.passage_axis(cell) == Some(East) => &[East, West],
+            _ if self.passage_axis(cell).is_some() => &[North, South],
+            _ if self.floors > 1 => match (floor > 0, floor + 1 < self.floors) {
+                (false, _) => &[North, East, South, West, Up],
+                (true, false) => &[North, East, South, West, Down],
+                (true, true) => &[North, East, South, West, Up, Down],
+            },
+            Topology::Polar => match self.coords(cell).1 {
+                0 => {
+                    let all: &'static [Direction] = &Direction::ALL;
+                    &all[..if self.height > 1 { self.row_len(1) } else { 0 }]
+                }
+                ring if ring + 1 < self.height && self.row_len(ring + 1) > self.row_len(ring) => {
+                    &[North, West, South, SouthEast, East]
+                }
+                _ => &[North, West, South, East],
+            },
+            topology => topology.directions(),
+        };
+        sides.iter().copied()
+    }
+
+    /// The cell on the `dir` side of `cell`, if it is inside the maze.
+    pub fn neighbor(&self, cell: usize, dir: Direction) -> Option<usize> {
+        if !self.is_enabled(cell) {
+            return None;
+        }
+        let floor = self.floor(cell);
+        match dir {
+            Direction::Up => {
+                return (floor + 1 < self.floors).then_some(cell + self.width * self.height)
+            }
+            Direction::Down => return (floor > 0).then(|| cell - self.width * self.height),
+            _ if self.topology == Topology::Polar => return self.polar_neighbor(cell, dir),
+            _ => {}
+        }
+        if self
+            .passage_axis(cell)
+            .is_some_and(|along| along != axis(dir))
+        {
+            return None;
+        }
+        let (nx, ny) = self.topology.step(self.coords(cell), dir)?;
+        let nx = usize::try_from(nx).ok().filter(|&nx| nx < self.width)?;
+        let ny = usize::try_from(ny).ok().filter(|&ny| ny < self.height)?;
+        let next = self.cell_on(floor, nx, ny);
+        if !self.is_enabled(next) {
+            return None;
+        }
+        // Heading along a tunnel leads into it rather than onto the crossing.
+        match self.crossings.get(&next) {
+            Some(&tunnel) if self.passage_axis(tunnel) == Some(axis(dir)) => Some(tunnel),
+            _ => Some(next),
+        }
+    }
+
+    /// Runs a tunnel under `cell` in the direction `along` and back,
+    /// turning `cell` into a crossing whose sides only lead across the
+    /// tunnel. The tunnel is a new cell with the coordinates of `cell`,
+    /// and the cells on either end of it reach the tunnel rather than the
+    /// crossing.
+    ///
+    /// Returns the tunnel, or `None` and leaves the maze untouched if it
+    /// is not a square maze with a single floor, `cell` already has
+    /// passages or is a crossing or tunnel, or the tunnel would lead out of
+    /// the maze.
+    pub fn add_tunnel(&mut self, cell: usize, along: Direction) -> Option<usize> {
+        let along = match along {
+            Direction::East | Direction::West => Direction::East,
+            Direction::North | Direction::South => Direction::South,
+            _ => return None,
+        };
+        let fits = self.topology == Topology::Square
+            && self.floors == 1
+            && self.cells[cell] == 0
+            && self.passage_axis(cell).is_none()
+            && self.neighbor(cell, along).is_some()
+            && self.neighbor(cell, along.opposite()).is_some();
+        if !fits {
+            return None;
+        }
+        let tunnel = self.cells.len();
+        self.cells.push(0);
+        self.tunnels.push((cell, along));
+        self.crossings.insert(cell, tunnel);
+        Some(tunnel)
+    }
+
+    /// The crossing above `cell` and the way the tunnel runs, east or
+    /// south, if `cell` is a tunnel.
+    pub fn tunnel(&self, cell: usize) -> Option<(usize, Direction)> {
+        let first = self.cells.len() - self.tunnels.len();
+        self.tunnels.get(cell.checked_sub(first)?).copied()
+    }
+
+    /// The tunnel under `cell`, if it is a crossing.
+    pub fn tunnel_under(&self, cell: usize) -> Option<usize> {
+        self.crossings.get(&cell).copied()
+    }
+
+    pub fn tunnel_count(&self) -> usize {
+        self.tunnels.len()
+    }
+
+    /// Whether `cell` is part of the maze rather than a hole left by a
+    /// mask, see [`Maze::with_mask`].
+    pub fn is_enabled(&self, cell: usize) -> bool {
+        !self.holes.get(cell).copied().unwrap_or(false)
+    }
+
+    pub fn hole_count(&self) -> usize {
+        self.holes.iter().filter(|&&hole| hole).count()
+    }
+
+    /// Whether this is a plain grid of square cells on a single floor with
+    /// neither tunnels nor holes, the only kind text and level codes can
+    /// hold.
+    pub fn check_plain(&self) -> Result<(), Unsupported> {
+        if self.topology != Topology::Square {
+            Err(Unsupported::Topology(self.topology))
+        } else if self.floors > 1 {
+            Err(Unsupported::Floors)
+        } else if !self.tunnels.is_empty() {
+            Err(Unsupported::Tunnels)
+        } else if self.hole_count() > 0 {
+            Err(Unsupported::Holes)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The way the only passage through a crossing or tunnel runs, east or
+    /// south.
+    fn passage_axis(&self, cell: usize) -> Option<Direction> {
+        if let Some((_, along)) = self.tunnel(cell) {
+            return Some(along);
+        }
+        let (_, along) = self.tunnel(self.tunnel_under(cell)?)?;
+        Some(axis(along.left()))
+    }
+
+    fn polar_neighbor(&self, cell: usize, dir: Direction) -> Option<usize> {
+        let (x, ring) = self.coords(cell);
+        let last = ring + 1 == self.height;
+        if ring == 0 {
+            let i = dir as usize;
+            return (!last && i < self.row_len(1)).then(|| self.cell(i, 1));
+        }
+        let len = self.row_len(ring);
+        match dir {
+            Direction::North if ring == 1 => Some(0),
+            Direction::North => Some(self.cell(x * self.row_len(ring - 1) / len, ring - 1)),
+            Direction::East => Some(self.cell((x + 1) % len, ring)),
+            Direction::West => Some(self.cell((x + len - 1) % len, ring)),
+            Direction::South if !last => {
+                let split = self.row_len(ring + 1) / len;
+                Some(self.cell(x * split, ring + 1))
+            }
+            Direction::SouthEast if !last && self.row_len(ring + 1) > len => {
+                Some(self.cell(x * 2 + 1, ring + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// All cells adjacent to `cell`, whether or not a wall separates them.
+    pub fn neighbors(&self, cell: usize) -> impl Iterator<Item = (Direction, usize)> + '_ {
+        self.sides(cell)
+            .filter_map(move |dir| self.neighbor(cell, dir).map(|n| (dir, n)))
+    }
+
+    /// The side of the neighbor on the `dir` side of `cell` that leads back
+    /// to it. Always the opposite direction, except in polar mazes.
+    pub fn back(&self, cell: usize, dir: Direction) -> Option<Direction> {
+        let other = self.neighbor(cell, dir)?;
+        match self.topology {
+            Topology::Polar => self.direction_to(other, cell),
+            _ => Some(dir.opposite()),
+        }
+    }
+
+    /// The side of `cell` that `other` lies on, if they are adjacent.
+    pub fn direction_to(&self, cell: usize, other: usize) -> Option<Direction> {
+        self.neighbors(cell)
+            .find(|&(_, n)| n == other)
+            .map(|(dir, _)| dir)
+    }
+
+    /// Adjacent cells reachable from `cell` through a passage.
+    pub fn links(&self, cell: usize) -> impl Iterator<Item = (Direction, usize)> + '_ {
+        self.neighbors(cell)
+            .filter(move |&(dir, _)| self.is_open(cell, dir))
+    }
+
+    pub fn is_open(&self, cell: usize, dir: Direction) -> bool {
+        self.cells[cell] & dir.bit() != 0
+    }
+
+    pub fn has_wall(&self, cell: usize, dir: Direction) -> bool {
+        !self.is_open(cell, dir)
+    }
+
+    /// The walled sides of `cell` as a bitmask of [`Direction::bit`]s.
+    pub fn wall_mask(&self, cell: usize) -> u16 {
+        let sides = self.sides(cell).fold(0, |mask, dir| mask | dir.bit());
+        !self.cells[cell] & sides
+    }
+
+    /// Number of passages leading out of `cell`.
+    pub fn degree(&self, cell: usize) -> usize {
+        self.cells[cell].count_ones() as usize
+    }
+
+    /// Knocks down the wall on the `dir` side of `cell`.
+    ///
+    /// Returns `false` and leaves the maze untouched if that side is the
+    /// outer boundary.
+    pub fn carve(&mut self, cell: usize, dir: Direction) -> bool {
+        let (Some(other), Some(back)) = (self.neighbor(cell, dir), self.back(cell, dir)) else {
+            return false;
+        };
+        self.cells[cell] |= dir.bit();
+        self.cells[other] |= back.bit();
+        true
+    }
+
+    /// Adds `rows` fully walled rows along the south edge. The cells already
+    /// there keep their indices and passages, and the start and goal stay
+    /// where they are.
+    ///
+    /// Panics for polar mazes and for mazes with several floors or with
+    /// tunnels, whose cells would have to move.
+    pub fn add_rows(&mut self, rows: usize) {
+        assert_ne!(self.topology, Topology::Polar, "polar mazes cannot grow");
+        assert_eq!(self.floors, 1, "mazes with several floors cannot grow");
+        assert!(self.tunnels.is_empty(), "weave mazes cannot grow");
+        self.height += rows;
+        self.cells.resize(self.width * self.height, 0);
+        if !self.holes.is_empty() {
+            self.holes.resize(self.cells.len(), false);
+        }
+    }
+
+    /// Puts the wall on the `dir` side of `cell` back up.
+    pub fn build_wall(&mut self, cell: usize, dir: Direction) {
+        self.cells[cell] &= !dir.bit();
+        if let (Some(other), Some(back)) = (self.neighbor(cell, dir), self.back(cell, dir)) {
+            self.cells[other] &= !back.bit();
+        }
+    }
+
+    /// Total number of passages between cells.
+    pub fn passage_count(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|c| c.count_ones() as usize)
+            .sum::<usize>()
+            / 2
+    }
+
+    /// Every wall segment exactly once, as the cell it bounds and its side.
+    ///
+    /// Inner walls are reported from the cell with the higher index (the
+    /// one to their south or east in a square maze), the outer boundary
+    /// from the cells along it.
+    pub fn walls(&self) -> impl Iterator<Item = (usize, Direction)> + '_ {
+        self.cells().flat_map(move |cell| {
+            self.sides(cell).filter_map(move |dir| {
+                let owned = match self.neighbor(cell, dir) {
+                    Some(n) => n < cell,
+                    None => true,
+                };
+                (owned && self.has_wall(cell, dir)).then_some((cell, dir))
+            })
+        })
+    }
+}
+
+/// What keeps a maze from being written in a form that only holds plain
+/// square grids, see [`Maze::check_plain`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unsupported {
+    /// The cells are not square.
+    Topology(Topology),
+    /// The maze has several floors.
+    Floors,
+    /// The maze is woven with tunnels.
+    Tunnels,
+    /// The maze is masked.
+    Holes,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsupported::Topology(topology) => {
+                write!(f, "only square mazes are supported, not {topology:?}")
+            }
+            Unsupported::Floors => write!(f, "mazes with several floors are not supported"),
+            Unsupported::Tunnels => write!(f, "woven mazes are not supported"),
+            Unsupported::Holes => write!(f, "masked mazes are not supported"),
+        }
+    }
+}
+
+impl std::error::Error for Unsupported {}
+
+/// The number of cells in every ring of a polar maze with `width` cells
+/// around the center, from the center outwards.
+fn ring_lens(width: usize) -> impl Iterator<Item = usize> {
+    let mut len = width;
+    std::iter::once(1).chain((1..).map(move |ring| {
+        // Split the cells once they are half again as wide as deep.
+        if ring > 1 && std::f64::consts::TAU * ring as f64 >= 1.5 * len as f64 {
+            len *= 2;
+        }
+        len
+    }))
+}
+
+/// East for either direction along a row, south along a column.
+fn axis(dir: Direction) -> Direction {
+    match dir {
+        Direction::West => Direction::East,
+        Direction::North => Direction::South,
+        _ => dir,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_maze_is_fully_walled() {
+        let maze = Maze::new(4, 3);
+        assert_eq!(maze.cell_count(), 12);
+        assert_eq!(maze.passage_count(), 0);
+        assert!(maze.cells().all(|c| maze.wall_mask(c) == 0b1111));
+        // 4 * 3 cells: 5 vertical lines of 3 walls, 4 horizontal lines of 4.
+        assert_eq!(maze.walls().count(), 5 * 3 + 4 * 4);
+    }
+
+    #[test]
+    fn neighbors_stay_in_bounds() {
+        let maze = Maze::new(3, 3);
+        assert_eq!(maze.neighbors(maze.cell(0, 0)).count(), 2);
+        assert_eq!(maze.neighbors(maze.cell(1, 0)).count(), 3);
+        assert_eq!(maze.neighbors(maze.cell(1, 1)).count(), 4);
+        assert_eq!(maze.neighbor(maze.cell(2, 2), Direction::East), None);
+        assert_eq!(
+            maze.neighbor(maze.cell(1, 1), Direction::North),
+            Some(maze.cell(1, 0))
+        );
+    }
+
+    #[test]
+    fn carving_opens_both_sides() {
+        let mut maze = Maze::new(2, 2);
+        let a = maze.cell(0, 0);
+        let b = maze.cell(1, 0);
+        assert!(maze.carve(a, Direction::East));
+        assert!(maze.is_open(a, Direction::East));
+        assert!(maze.is_open(b, Direction::West));
+        assert_eq!(maze.links(b).collect::<Vec<_>>(), [(Direction::West, a)]);
+        assert_eq!(maze.passage_count(), 1);
+
+        maze.build_wall(b, Direction::West);
+        assert!(maze.has_wall(a, Direction::East));
+        assert_eq!(maze.passage_count(), 0);
+    }
+
+    #[test]
+    fn hex_cells_have_six_sides() {
+        let mut maze = Maze::with_topology(Topology::Hex, 4, 3);
+        // Odd columns sit half a cell lower than even ones.
+        assert_eq!(maze.neighbors(maze.cell(1, 1)).count(), 6);
+        assert_eq!(maze.neighbors(maze.cell(2, 1)).count(), 6);
+        assert_eq!(maze.neighbors(maze.cell(0, 0)).count(), 2);
+        assert_eq!(maze.neighbors(maze.cell(1, 0)).count(), 5);
+        assert_eq!(
+            maze.neighbor(maze.cell(1, 0), Direction::NorthEast),
+            Some(maze.cell(2, 0))
+        );
+        assert_eq!(
+            maze.neighbor(maze.cell(2, 0), Direction::SouthWest),
+            Some(maze.cell(1, 0))
+        );
+        assert_eq!(maze.neighbor(0, Direction::East), None);
+
+        let edges = maze
+            .cells()
+            .map(|c| maze.neighbors(c).count())
+            .sum::<usize>()
+            / 2;
+        let sides = maze.cell_count() * 6;
+        // Every inner edge is shared by two cells, the rest is boundary.
+        assert_eq!(maze.walls().count(), sides - edges);
+
+        assert!(maze.carve(maze.cell(1, 1), Direction::SouthEast));
+        assert!(maze.is_open(maze.cell(2, 2), Direction::NorthWest));
+        assert_eq!(maze.wall_mask(maze.cell(2, 2)), 0b0111_0101);
+        assert_eq!(
+            maze.direction_to(maze.cell(2, 2), maze.cell(1, 1)),
+            Some(Direction::NorthWest)
+        );
+    }
+
+    #[test]
+    fn polar_rings_grow_outwards() {
+        use Direction::*;
+        let mut maze = Maze::with_topology(Topology::Polar, 6, 5);
+        let rings = (0..5).map(|ring| maze.row_len(ring)).collect::<Vec<_>>();
+        assert_eq!(rings, [1, 6, 12, 24, 24]);
+        assert_eq!(maze.cell_count(), 67);
+        assert_eq!((maze.start(), maze.goal()), (maze.cell(0, 4), 0));
+        assert_eq!(maze.coords(maze.cell(13, 3)), (13, 3));
+
+        // The center borders the whole first ring.
+        assert_eq!(maze.neighbors(0).count(), 6);
+        assert_eq!(maze.neighbor(0, Direction::ALL[5]), Some(maze.cell(5, 1)));
+        assert_eq!(maze.neighbor(maze.cell(5, 1), North), Some(0));
+        assert_eq!(maze.neighbor(maze.cell(0, 2), West), Some(maze.cell(11, 2)));
+        // Cells of the second ring split in two towards the third.
+        let cell = maze.cell(5, 2);
+        assert_eq!(
+            maze.sides(cell).collect::<Vec<_>>(),
+            [North, West, South, SouthEast, East]
+        );
+        assert_eq!(maze.neighbor(cell, South), Some(maze.cell(10, 3)));
+        assert_eq!(maze.neighbor(cell, SouthEast), Some(maze.cell(11, 3)));
+        assert_eq!(maze.back(cell, SouthEast), Some(North));
+        assert_eq!(maze.neighbor(maze.cell(11, 3), North), Some(cell));
+        assert_eq!(maze.sides(maze.cell(3, 3)).count(), 4);
+        assert_eq!(maze.neighbor(maze.cell(3, 4), South), None);
+
+        let edges = maze
+            .cells()
+            .map(|c| maze.neighbors(c).count())
+            .sum::<usize>()
+            / 2;
+        let sides = maze.cells().map(|c| maze.sides(c).count()).sum::<usize>();
+        assert_eq!(maze.walls().count(), sides - edges);
+
+        assert!(maze.carve(maze.cell(2, 1), North));
+        assert!(maze.is_open(0, Direction::ALL[2]));
+        assert_eq!(maze.wall_mask(0), 0b0011_1011);
+        assert!(maze.carve(cell, SouthEast));
+        assert!(maze.is_open(maze.cell(11, 3), North));
+    }
+
+    #[test]
+    fn floors_stack_up() {
+        use Direction::*;
+        let mut maze = Maze::with_floors(3, 2, 3);
+        assert_eq!(maze.cell_count(), 18);
+        let cell = maze.cell_on(1, 2, 1);
+        assert_eq!((maze.coords(cell), maze.floor(cell)), ((2, 1), 1));
+        assert_eq!(maze.neighbor(cell, Up), Some(maze.cell_on(2, 2, 1)));
+        assert_eq!(maze.neighbor(cell, Down), Some(maze.cell(2, 1)));
+        assert_eq!(maze.neighbor(cell, East), None);
+        assert_eq!(maze.neighbor(maze.cell_on(1, 2, 0), South), Some(cell));
+        assert_eq!(maze.sides(0).count(), 5);
+        assert_eq!(maze.sides(cell).count(), 6);
+        assert!(!maze.carve(0, Down));
+        assert!(maze.carve(cell, Up));
+        assert!(maze.is_open(maze.cell_on(2, 2, 1), Down));
+        assert_eq!(maze.wall_mask(cell), 0b10_0000_1111);
+    }
+
+    #[test]
+    fn carving_the_boundary_is_refused() {
+        let mut maze = Maze::new(2, 2);
+        assert!(!maze.carve(0, Direction::North));
+        assert!(!maze.carve(0, Direction::West));
+        assert_eq!(maze.passage_count(), 0);
+    }
+}
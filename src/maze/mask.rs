@@ -0,0 +1,372 @@
+//! Masks that cut mazes into arbitrary shapes, such as letters or logos.
+//!
+//! A mask tells which cells of a grid belong to the maze; the others are
+//! holes, see [`Maze::with_mask`]. Masks come from a grid of `bool`s, from
+//! text or from pictures, see
+//! [`picture::import_mask`](crate::picture::import_mask). In text every
+//! line is a row, with `#` for a cell of the maze and `.` or a space for a
+//! hole:
+//!
+//! ```text
+//! ####.
+//! #..##
+//! ####.
+//! ```
+//!
+//! Shorter lines are padded with holes. Generators only fill connected
+//! shapes, so [`Mask::generate`] either rejects masks that fall apart into
+//! several regions or bridges the gaps between them.
+
+use std::{collections::VecDeque, fmt};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::{generate::MazeGenerator, Maze, Topology};
+
+/// The cells of a `width` by `height` grid that make up a maze.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+}
+
+/// What [`Mask::generate`] does with a mask whose cells fall apart into
+/// separate regions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Regions {
+    /// Fail with [`MaskError::Disconnected`].
+    #[default]
+    Reject,
+    /// Join them with corridors through the fewest holes, see
+    /// [`Mask::bridge`].
+    Bridge,
+}
+
+/// Why a mask could not be read or filled with a maze.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MaskError {
+    /// A character other than `#`, `.` or a space. Lines and columns count
+    /// from 1, columns in characters.
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    /// The mask has no cells.
+    Empty,
+    /// The cells fall apart into several regions. Holds the first cell of
+    /// every region, as column and row.
+    Disconnected(Vec<(usize, usize)>),
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskError::UnexpectedChar {
+                line,
+                column,
+                character,
+            } => write!(f, "line {line}, column {column}: unexpected {character:?}"),
+            MaskError::Empty => write!(f, "the mask has no cells"),
+            MaskError::Disconnected(regions) => {
+                write!(f, "the mask falls apart into {} regions at", regions.len())?;
+                for (x, y) in regions {
+                    write!(f, " ({x}, {y})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+impl Mask {
+    /// A mask enabling every cell.
+    pub fn new(width: usize, height: usize) -> Self {
+        Mask {
+            width,
+            height,
+            cells: vec![true; width * height],
+        }
+    }
+
+    /// A mask from rows of cells, `true` for the cells of the maze. Short
+    /// rows are padded with holes.
+    pub fn from_grid(rows: &[Vec<bool>]) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut mask = Mask {
+            width,
+            height: rows.len(),
+            cells: vec![false; width * rows.len()],
+        };
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &enabled) in row.iter().enumerate() {
+                mask.set(x, y, enabled);
+            }
+        }
+        mask
+    }
+
+    /// Reads the text form described in the [module docs](self). Blank
+    /// lines at the end are ignored.
+    pub fn from_text(source: &str) -> Result<Self, MaskError> {
+        let mut rows = source
+            .lines()
+            .enumerate()
+            .map(|(line, text)| {
+                text.trim_end()
+                    .chars()
+                    .enumerate()
+                    .map(|(column, character)| match character {
+                        '#' => Ok(true),
+                        '.' | ' ' => Ok(false),
+                        _ => Err(MaskError::UnexpectedChar {
+                            line: line + 1,
+                            column: column + 1,
+                            character,
+                        }),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        while rows.last().is_some_and(Vec::is_empty) {
+            rows.pop();
+        }
+        Ok(Mask::from_grid(&rows))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_enabled(&self, x: usize, y: usize) -> bool {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, enabled: bool) {
+        assert!(x < self.width && y < self.height);
+        self.cells[y * self.width + x] = enabled;
+    }
+
+    /// Number of cells of the maze.
+    pub fn enabled_count(&self) -> usize {
+        self.cells.iter().filter(|&&enabled| enabled).count()
+    }
+
+    /// The cells as separate regions, each listed row by row, in the order
+    /// of their first cells. Which cells touch depends on `topology`;
+    /// polar mazes panic as in [`Maze::with_mask`].
+    pub fn regions(&self, topology: Topology) -> Vec<Vec<(usize, usize)>> {
+        if self.enabled_count() == 0 {
+            return Vec::new();
+        }
+        let maze = Maze::with_mask(topology, self);
+        let mut region = vec![None; maze.cell_count()];
+        let mut regions = Vec::<Vec<usize>>::new();
+        for first in maze.cells().filter(|&cell| maze.is_enabled(cell)) {
+            if region[first].is_some() {
+                continue;
+            }
+            region[first] = Some(regions.len());
+            let mut cells = vec![first];
+            let mut next = 0;
+            while let Some(&cell) = cells.get(next) {
+                next += 1;
+                for (_, n) in maze.neighbors(cell) {
+                    if region[n].is_none() {
+                        region[n] = Some(regions.len());
+                        cells.push(n);
+                    }
+                }
+            }
+            cells.sort_unstable();
+            regions.push(cells);
+        }
+        regions
+            .into_iter()
+            .map(|cells| cells.into_iter().map(|cell| maze.coords(cell)).collect())
+            .collect()
+    }
+
+    /// Joins all regions into one by turning holes into cells, always
+    /// taking the shortest corridor from the region of the first cell to
+    /// the nearest other one. Returns the number of holes filled.
+    pub fn bridge(&mut self, topology: Topology) -> usize {
+        let grid = Maze::with_topology(topology, self.width, self.height);
+        let mut filled = 0;
+        loop {
+            let regions = self.regions(topology);
+            if regions.len() < 2 {
+                return filled;
+            }
+            let index = |(x, y): (usize, usize)| grid.cell(x, y);
+            let mut from = vec![None; grid.cell_count()];
+            let mut queue = regions[0]
+                .iter()
+                .map(|&c| index(c))
+                .collect::<VecDeque<_>>();
+            for &cell in &queue {
+                from[cell] = Some(cell);
+            }
+            // Search outwards through the holes until reaching another
+            // region, then fill the holes along the way back.
+            let reached = 'search: loop {
+                let cell = queue
+                    .pop_front()
+                    .expect("every region can be reached through the holes");
+                for (_, n) in grid.neighbors(cell) {
+                    if from[n].is_some() {
+                        continue;
+                    }
+                    from[n] = Some(cell);
+                    let (x, y) = grid.coords(n);
+                    if self.is_enabled(x, y) {
+                        break 'search cell;
+                    }
+                    queue.push_back(n);
+                }
+            };
+            let mut cell = reached;
+            loop {
+                let (x, y) = grid.coords(cell);
+                if self.is_enabled(x, y) {
+                    break;
+                }
+                self.set(x, y, true);
+                filled += 1;
+                cell = from[cell].expect("the search came this way");
+            }
+        }
+    }
+
+    /// Carves a perfect maze into the cells of the mask with `generator`.
+    /// The start is the first cell and the goal the last one, and the same
+    /// seed always gives the same layout.
+    ///
+    /// Panics for polar mazes as in [`Maze::with_mask`].
+    pub fn generate(
+        &self,
+        generator: &dyn MazeGenerator,
+        topology: Topology,
+        seed: u64,
+        regions: Regions,
+    ) -> Result<Maze, MaskError> {
+        if self.enabled_count() == 0 {
+            return Err(MaskError::Empty);
+        }
+        let mut mask = self.clone();
+        match regions {
+            Regions::Reject => {
+                let regions = self.regions(topology);
+                if regions.len() > 1 {
+                    return Err(MaskError::Disconnected(
+                        regions.iter().map(|cells| cells[0]).collect(),
+                    ));
+                }
+            }
+            Regions::Bridge => {
+                mask.bridge(topology);
+            }
+        }
+        let mut maze = Maze::with_mask(topology, &mask);
+        generator.carve(&mut maze, &mut StdRng::seed_from_u64(seed));
+        Ok(maze)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate, solve::distances};
+
+    /// A ring around a hole with a tail, in the text form.
+    const SHAPE: &str = "\
+.#####
+.#...#
+.#...##
+.#####.
+...#
+...####
+";
+
+    fn assert_perfect_inside(maze: &Maze) {
+        let distances = distances(maze, maze.start());
+        for cell in maze.cells() {
+            assert_eq!(distances[cell].is_some(), maze.is_enabled(cell), "{cell}");
+        }
+        assert!(maze.is_enabled(maze.goal()));
+        assert_eq!(
+            maze.passage_count(),
+            maze.cell_count() - maze.hole_count() - 1
+        );
+    }
+
+    #[test]
+    fn reads_text_masks() {
+        let mask = Mask::from_text(SHAPE).unwrap();
+        assert_eq!((mask.width(), mask.height()), (7, 6));
+        assert_eq!(mask.enabled_count(), 20);
+        assert!(mask.is_enabled(6, 2) && !mask.is_enabled(6, 3));
+        assert!(!mask.is_enabled(0, 0));
+        assert_eq!(
+            Mask::from_text("##\n#x#\n"),
+            Err(MaskError::UnexpectedChar {
+                line: 2,
+                column: 2,
+                character: 'x'
+            })
+        );
+        assert_eq!(
+            Mask::from_grid(&[vec![true], vec![false, true]]),
+            Mask::from_text("#\n.#").unwrap()
+        );
+    }
+
+    #[test]
+    fn every_generator_fills_the_shape() {
+        let mask = Mask::from_text(SHAPE).unwrap();
+        for name in generate::NAMES {
+            let generator = generate::by_name(name).unwrap();
+            for topology in [Topology::Square, Topology::Hex] {
+                for seed in 0..8 {
+                    let maze = mask
+                        .generate(generator.as_ref(), topology, seed, Regions::Reject)
+                        .unwrap();
+                    assert_eq!(maze.hole_count(), 7 * 6 - 20);
+                    assert_eq!(maze.start(), maze.cell(1, 0));
+                    assert_eq!(maze.goal(), maze.cell(6, 5));
+                    assert_perfect_inside(&maze);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn split_masks_are_rejected_or_bridged() {
+        let mask = Mask::from_text("##..##\n##..##\n......\n.....#").unwrap();
+        assert_eq!(mask.regions(Topology::Square).len(), 3);
+        assert_eq!(
+            mask.generate(&generate::Kruskal, Topology::Square, 1, Regions::Reject),
+            Err(MaskError::Disconnected(vec![(0, 0), (4, 0), (5, 3)]))
+        );
+        assert_eq!(
+            Mask::new(0, 0).generate(&generate::Prim, Topology::Hex, 1, Regions::Reject),
+            Err(MaskError::Empty)
+        );
+
+        let mut bridged = mask.clone();
+        assert_eq!(bridged.bridge(Topology::Square), 3);
+        assert_eq!(bridged.regions(Topology::Square).len(), 1);
+        let maze = mask
+            .generate(&generate::Wilson, Topology::Square, 1, Regions::Bridge)
+            .unwrap();
+        assert_eq!(maze.hole_count(), 24 - 12);
+        assert_perfect_inside(&maze);
+    }
+}
@@ -0,0 +1,23 @@
+//! Maze data model.
+//!
+//! Nothing in here depends on Bevy, so mazes can be built and tested
+//! headlessly.
+
+pub mod ascii;
+pub mod braid;
+pub mod chunks;
+pub mod classic;
+pub mod code;
+mod disjoint_set;
+pub mod floors;
+pub mod generate;
+mod grid;
+pub mod mask;
+mod runs;
+pub mod solve;
+mod topology;
+pub mod weave;
+
+pub use grid::{Direction, Maze, Unsupported};
+pub use runs::{wall_runs, wall_runs_in, WallRun};
+pub use topology::Topology;
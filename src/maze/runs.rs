@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+use super::{Direction, Maze, Topology};
+
+/// A straight stretch of consecutive wall segments along one grid line.
+///
+/// Grid lines are numbered like cell borders: horizontal line `r` runs
+/// along the north side of row `r` (and line `height` along the south side
+/// of the last row), vertical line `c` along the west side of column `c`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallRun {
+    /// Whether the run goes along a row (east-west) rather than a column.
+    pub horizontal: bool,
+    /// Index of the grid line the run lies on.
+    pub line: usize,
+    /// First cell along the line the run borders.
+    pub from: usize,
+    /// Number of cells the run spans.
+    pub len: usize,
+    /// Chunk the run belongs to, in units of `chunk_size` cells.
+    pub chunk: (usize, usize),
+}
+
+/// Merges the walls of `maze` into maximal straight runs.
+///
+/// Runs never cross a chunk border, so every run belongs to exactly one
+/// `chunk_size` by `chunk_size` block of cells. Only square mazes have
+/// straight grid lines; other topologies panic, as do mazes with tunnels.
+pub fn wall_runs(maze: &Maze, chunk_size: usize) -> Vec<WallRun> {
+    wall_runs_in(maze, 0..maze.height(), chunk_size)
+}
+
+/// Like [`wall_runs`] for the walls of the rows in `rows` alone: the north
+/// sides of their cells and the walls between them, plus the south border
+/// if `rows` reaches the last row. Chunks still count from the first row.
+pub fn wall_runs_in(maze: &Maze, rows: Range<usize>, chunk_size: usize) -> Vec<WallRun> {
+    assert!(chunk_size > 0);
+    assert_eq!(
+        maze.topology(),
+        Topology::Square,
+        "only square mazes have wall runs"
+    );
+    assert_eq!(maze.tunnel_count(), 0, "crossings break up wall runs");
+    let (width, height) = (maze.width(), maze.height());
+    let mut runs = Vec::new();
+
+    let mut collect = |horizontal: bool, line: usize, cells: Range<usize>, lines: usize| {
+        let mut run: Option<WallRun> = None;
+        for along in cells {
+            let wall = if horizontal {
+                horizontal_wall(maze, along, line)
+            } else {
+                vertical_wall(maze, line, along)
+            };
+            let across = line.min(lines - 1) / chunk_size;
+            let chunk = if horizontal {
+                (along / chunk_size, across)
+            } else {
+                (across, along / chunk_size)
+            };
+            match &mut run {
+                Some(r) if wall && r.chunk == chunk => r.len += 1,
+                _ => {
+                    runs.extend(run.take());
+                    if wall {
+                        run = Some(WallRun {
+                            horizontal,
+                            line,
+                            from: along,
+                            len: 1,
+                            chunk,
+                        });
+                    }
+                }
+            }
+        }
+        runs.extend(run);
+    };
+    let south_border = usize::from(rows.end == height);
+    for line in rows.start..rows.end + south_border {
+        collect(true, line, 0..width, height);
+    }
+    for line in 0..=width {
+        collect(false, line, rows.clone(), width);
+    }
+    runs
+}
+
+/// Whether the north side of cell `x` in row `line` is walled, or the south
+/// side of the last row when `line` is the height. There are no walls
+/// between two holes.
+pub(super) fn horizontal_wall(maze: &Maze, x: usize, line: usize) -> bool {
+    let below = (line < maze.height()).then(|| maze.cell(x, line));
+    let above = line.checked_sub(1).map(|y| maze.cell(x, y));
+    walled(maze, below, Direction::North) || walled(maze, above, Direction::South)
+}
+
+/// Whether the west side of cell `y` in column `line` is walled, or the
+/// east side of the last column when `line` is the width. There are no
+/// walls between two holes.
+pub(super) fn vertical_wall(maze: &Maze, line: usize, y: usize) -> bool {
+    let east = (line < maze.width()).then(|| maze.cell(line, y));
+    let west = line.checked_sub(1).map(|x| maze.cell(x, y));
+    walled(maze, east, Direction::West) || walled(maze, west, Direction::East)
+}
+
+fn walled(maze: &Maze, cell: Option<usize>, dir: Direction) -> bool {
+    cell.is_some_and(|cell| maze.is_enabled(cell) && maze.has_wall(cell, dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate::recursive_backtracker, mask::Mask};
+
+    #[test]
+    fn runs_cover_every_wall_once() {
+        for chunk_size in [1, 3, 8, 100] {
+            let maze = recursive_backtracker(20, 13, 5);
+            let runs = wall_runs(&maze, chunk_size);
+            let total = runs.iter().map(|r| r.len).sum::<usize>();
+            assert_eq!(total, maze.walls().count());
+            assert!(runs.len() <= maze.walls().count());
+        }
+    }
+
+    #[test]
+    fn holes_are_only_outlined() {
+        let mask = Mask::from_text("####\n#..#\n####").unwrap();
+        let maze = Maze::with_mask(Topology::Square, &mask);
+        let runs = wall_runs(&maze, 100);
+        let total = runs.iter().map(|r| r.len).sum::<usize>();
+        // Every wall of the grid but the one between the two holes.
+        assert_eq!(total, 4 * 4 + 5 * 3 - 1);
+        assert_eq!(total, maze.walls().count());
+    }
+
+    #[test]
+    fn row_ranges_split_the_runs() {
+        let maze = recursive_backtracker(9, 11, 2);
+        let count = |rows| {
+            wall_runs_in(&maze, rows, 4)
+                .iter()
+                .map(|r| r.len)
+                .sum::<usize>()
+        };
+        assert_eq!(
+            count(0..4) + count(4..5) + count(5..11),
+            maze.walls().count()
+        );
+        assert_eq!(count(3..3), 0);
+        assert!(wall_runs_in(&maze, 4..5, 4)
+            .iter()
+            .all(|r| if r.horizontal {
+                r.line == 4
+            } else {
+                r.from == 4
+            }));
+    }
+
+    #[test]
+    fn closed_maze_merges_into_full_lines() {
+        let maze = Maze::new(5, 4);
+        let runs = wall_runs(&maze, 100);
+        assert_eq!(runs.len(), (4 + 1) + (5 + 1));
+        assert!(runs
+            .iter()
+            .all(|r| r.len == if r.horizontal { 5 } else { 4 }));
+    }
+
+    #[test]
+    fn runs_stay_inside_their_chunk() {
+        let maze = Maze::new(10, 10);
+        for run in wall_runs(&maze, 4) {
+            assert_eq!(run.from / 4, (run.from + run.len - 1) / 4);
+            let along = if run.horizontal {
+                run.chunk.0
+            } else {
+                run.chunk.1
+            };
+            assert_eq!(along, run.from / 4);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Shortest paths and distance maps.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use super::Maze;
+
+/// Search algorithm used by [`solve`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Search {
+    /// Breadth-first search.
+    Bfs,
+    /// A* with the distance on an empty grid as heuristic.
+    AStar,
+}
+
+/// Shortest path from `from` to `to`, including both ends, or `None` if
+/// `to` cannot be reached.
+pub fn solve(maze: &Maze, from: usize, to: usize, search: Search) -> Option<Vec<usize>> {
+    let came_from = match search {
+        Search::Bfs => bfs(maze, from, Some(to)).1,
+        Search::AStar => astar(maze, from, to),
+    };
+    if from != to && came_from[to].is_none() {
+        return None;
+    }
+    let mut path = vec![to];
+    while let Some(&cell) = path.last().filter(|&&c| c != from) {
+        path.push(came_from[cell]?);
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Number of steps from `from` to every cell, `None` for unreachable ones.
+pub fn distances(maze: &Maze, from: usize) -> Vec<Option<usize>> {
+    bfs(maze, from, None).0
+}
+
+/// The reachable cell furthest away from `from` and its distance.
+pub fn farthest(maze: &Maze, from: usize) -> (usize, usize) {
+    distances(maze, from)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(cell, d)| Some((cell, d?)))
+        .max_by_key(|&(cell, d)| (d, Reverse(cell)))
+        .expect("the start cell is always reachable")
+}
+
+/// Breadth-first search, stopping early once `target` is reached.
+fn bfs(
+    maze: &Maze,
+    from: usize,
+    target: Option<usize>,
+) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let mut distance = vec![None; maze.cell_count()];
+    let mut came_from = vec![None; maze.cell_count()];
+    let mut queue = VecDeque::from([from]);
+    distance[from] = Some(0);
+
+    while let Some(cell) = queue.pop_front() {
+        if Some(cell) == target {
+            break;
+        }
+        let d = distance[cell].unwrap_or_default();
+        for (_, next) in maze.links(cell) {
+            if distance[next].is_none() {
+                distance[next] = Some(d + 1);
+                came_from[next] = Some(cell);
+                queue.push_back(next);
+            }
+        }
+    }
+    (distance, came_from)
+}
+
+fn astar(maze: &Maze, from: usize, to: usize) -> Vec<Option<usize>> {
+    let heuristic = |cell: usize| {
+        maze.topology().distance(maze.coords(cell), maze.coords(to))
+            + maze.floor(cell).abs_diff(maze.floor(to))
+    };
+    let mut cost = vec![usize::MAX; maze.cell_count()];
+    let mut came_from = vec![None; maze.cell_count()];
+    let mut open = BinaryHeap::from([Reverse((heuristic(from), from))]);
+    cost[from] = 0;
+
+    while let Some(Reverse((_, cell))) = open.pop() {
+        if cell == to {
+            break;
+        }
+        for (_, next) in maze.links(cell) {
+            let next_cost = cost[cell] + 1;
+            if next_cost < cost[next] {
+                cost[next] = next_cost;
+                came_from[next] = Some(cell);
+                open.push(Reverse((next_cost + heuristic(next), next)));
+            }
+        }
+    }
+    came_from
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{
+        braid::Braid,
+        generate::{self, MazeGenerator},
+        Direction, Topology,
+    };
+
+    fn assert_valid_path(maze: &Maze, path: &[usize], from: usize, to: usize) {
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        for step in path.windows(2) {
+            assert!(maze.links(step[0]).any(|(_, n)| n == step[1]));
+        }
+    }
+
+    #[test]
+    fn bfs_and_astar_find_equally_short_paths() {
+        for seed in 0..10 {
+            let mut maze = generate::recursive_backtracker(15, 11, seed);
+            Braid {
+                dead_end_removal: 0.6,
+                extra_loops: 10,
+            }
+            .apply(&mut maze, seed);
+            let (from, to) = (maze.start(), maze.goal());
+            let bfs = solve(&maze, from, to, Search::Bfs).unwrap();
+            let astar = solve(&maze, from, to, Search::AStar).unwrap();
+            assert_valid_path(&maze, &bfs, from, to);
+            assert_valid_path(&maze, &astar, from, to);
+            assert_eq!(bfs.len(), astar.len());
+            assert_eq!(Some(bfs.len() - 1), distances(&maze, from)[to]);
+        }
+    }
+
+    #[test]
+    fn astar_is_optimal_on_other_grids() {
+        for (topology, width, seed) in [(Topology::Hex, 14), (Topology::Polar, 6)]
+            .into_iter()
+            .flat_map(|(topology, width)| (0..10).map(move |seed| (topology, width, seed)))
+        {
+            let mut maze = generate::Prim.generate_with(topology, width, 9, seed);
+            Braid {
+                dead_end_removal: 1.0,
+                extra_loops: 15,
+            }
+            .apply(&mut maze, seed);
+            let (from, to) = (maze.start(), maze.goal());
+            let astar = solve(&maze, from, to, Search::AStar).unwrap();
+            assert_valid_path(&maze, &astar, from, to);
+            assert_eq!(Some(astar.len() - 1), distances(&maze, from)[to]);
+        }
+    }
+
+    #[test]
+    fn corridor_path() {
+        let mut maze = Maze::new(4, 1);
+        for x in 0..3 {
+            maze.carve(x, Direction::East);
+        }
+        assert_eq!(solve(&maze, 0, 3, Search::Bfs), Some(vec![0, 1, 2, 3]));
+        assert_eq!(solve(&maze, 3, 1, Search::AStar), Some(vec![3, 2, 1]));
+        assert_eq!(solve(&maze, 2, 2, Search::Bfs), Some(vec![2]));
+        assert_eq!(farthest(&maze, 1), (3, 2));
+    }
+
+    #[test]
+    fn unreachable_cells() {
+        let mut maze = Maze::new(3, 1);
+        maze.carve(0, Direction::East);
+        assert_eq!(solve(&maze, 0, 2, Search::Bfs), None);
+        assert_eq!(solve(&maze, 0, 2, Search::AStar), None);
+        assert_eq!(distances(&maze, 0), [Some(0), Some(1), None]);
+    }
+
+    #[test]
+    fn generated_mazes_are_fully_reachable() {
+        for name in generate::NAMES {
+            let maze = generate::by_name(name).unwrap().generate(12, 12, 4);
+            assert!(distances(&maze, maze.start()).iter().all(Option::is_some));
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::Direction;
+
+/// The shape of the cells and how they fit together.
+///
+/// Cells are always addressed by column `x` and row `y`; the topology
+/// decides which cells are adjacent and through which sides. In polar
+/// mazes the rows are rings, see [`Topology::Polar`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Square cells with four sides.
+    #[default]
+    Square,
+    /// Flat-topped hexagons with six sides, in columns where every odd
+    /// column is shifted half a cell south. Neighbors lie to the north and
+    /// south and along the four diagonals.
+    Hex,
+    /// Concentric rings around a round center cell, for circular
+    /// labyrinths. Row 0 is the center, and `width` counts the cells of the
+    /// first ring around it, from 3 to 8. Further rings double their cells
+    /// whenever they would otherwise get twice as wide as they are deep.
+    ///
+    /// North leads inwards, east clockwise and west counter-clockwise seen
+    /// from above. Where the next ring splits a cell in two, south leads to
+    /// the outer cell on the west half and south-east to the one on the
+    /// east half. The center's sides are [`Direction::ALL`] in the order of
+    /// the first ring's cells. Cells have different sides depending on
+    /// their ring, see [`Maze::sides`](super::Maze::sides).
+    Polar,
+}
+
+impl Topology {
+    pub const ALL: [Topology; 3] = [Topology::Square, Topology::Hex, Topology::Polar];
+
+    /// The sides of a cell, clockwise starting from north. Every side a
+    /// polar cell can have, in no particular order.
+    pub fn directions(self) -> &'static [Direction] {
+        match self {
+            Topology::Square => &[
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ],
+            Topology::Hex => &[
+                Direction::North,
+                Direction::NorthEast,
+                Direction::SouthEast,
+                Direction::South,
+                Direction::SouthWest,
+                Direction::NorthWest,
+            ],
+            Topology::Polar => {
+                let all: &'static [Direction] = &Direction::ALL;
+                &all[..8]
+            }
+        }
+    }
+
+    /// Bitmask of all sides of a cell, see [`Direction::bit`].
+    pub fn side_mask(self) -> u16 {
+        self.directions()
+            .iter()
+            .fold(0, |mask, dir| mask | dir.bit())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Topology::Square => "square",
+            Topology::Hex => "hex",
+            Topology::Polar => "polar",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Topology> {
+        Topology::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Column and row of the cell on the `dir` side of column `x`, row `y`,
+    /// which may lie outside the maze. `None` if cells have no such side,
+    /// and always for polar mazes, whose rings differ in length.
+    pub fn step(self, (x, y): (usize, usize), dir: Direction) -> Option<(isize, isize)> {
+        let (x, y) = (x as isize, y as isize);
+        let (dx, dy) = match (self, dir) {
+            (
+                Topology::Square,
+                Direction::North | Direction::East | Direction::South | Direction::West,
+            ) => dir.offset(),
+            (Topology::Hex, Direction::North | Direction::South) => dir.offset(),
+            // Diagonal neighbors share the row of odd columns on the north
+            // side and of even columns on the south side.
+            (Topology::Hex, Direction::NorthEast | Direction::NorthWest) => {
+                (dir.offset().0, if x % 2 == 1 { 0 } else { -1 })
+            }
+            (Topology::Hex, Direction::SouthEast | Direction::SouthWest) => {
+                (dir.offset().0, if x % 2 == 1 { 1 } else { 0 })
+            }
+            _ => return None,
+        };
+        Some((x + dx, y + dy))
+    }
+
+    /// Fewest steps between two cells when there are no walls. Only a lower
+    /// bound for polar mazes.
+    pub fn distance(self, a: (usize, usize), b: (usize, usize)) -> usize {
+        match self {
+            Topology::Square => a.0.abs_diff(b.0) + a.1.abs_diff(b.1),
+            Topology::Hex => {
+                // Convert to axial coordinates, where the distance is the
+                // largest difference along the three hex axes.
+                let axial = |(x, y): (usize, usize)| {
+                    let (x, y) = (x as isize, y as isize);
+                    (x, y - (x - (x & 1)) / 2)
+                };
+                let ((q1, r1), (q2, r2)) = (axial(a), axial(b));
+                let (dq, dr) = (q1 - q2, r1 - r2);
+                dq.unsigned_abs()
+                    .max(dr.unsigned_abs())
+                    .max((dq + dr).unsigned_abs())
+            }
+            // Every step changes the ring by at most one.
+            Topology::Polar => a.1.abs_diff(b.1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_steps_are_symmetric() {
+        for x in 1..5 {
+            for y in 1..5 {
+                for &dir in Topology::Hex.directions() {
+                    let (nx, ny) = Topology::Hex.step((x, y), dir).unwrap();
+                    let back = Topology::Hex
+                        .step((nx as usize, ny as usize), dir.opposite())
+                        .unwrap();
+                    assert_eq!(back, (x as isize, y as isize), "{dir:?} from ({x}, {y})");
+                    assert_eq!(
+                        Topology::Hex.distance((x, y), (nx as usize, ny as usize)),
+                        1
+                    );
+                }
+            }
+        }
+        assert_eq!(Topology::Hex.step((0, 0), Direction::East), None);
+    }
+
+    #[test]
+    fn hex_distance() {
+        assert_eq!(Topology::Hex.distance((0, 0), (0, 3)), 3);
+        // Moving diagonally also moves half a row.
+        assert_eq!(Topology::Hex.distance((0, 0), (4, 2)), 4);
+        assert_eq!(Topology::Hex.distance((0, 0), (4, 3)), 5);
+        assert_eq!(Topology::Square.distance((0, 0), (4, 3)), 7);
+        assert_eq!(Topology::Polar.distance((0, 4), (11, 1)), 3);
+    }
+}
@@ -0,0 +1,227 @@
+//! Weave mazes, whose passages cross over and under each other.
+//!
+//! At a crossing one passage runs over a bridge while another passes
+//! through a tunnel underneath, see [`Maze::add_tunnel`]. The cells on
+//! either end of the bridge hold the ramps up to it, so their only
+//! passages are in line with the bridge. [`Maze::bridge`] finds them.
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::{disjoint_set::DisjointSet, Direction, Maze};
+
+impl Maze {
+    /// The two cells leading up to the bridge over `cell`, each with the
+    /// direction its ramp rises towards, if `cell` is a crossing laid out
+    /// as described in the [module docs](self).
+    pub fn bridge(&self, cell: usize) -> Option<[(usize, Direction); 2]> {
+        self.tunnel_under(cell)?;
+        let ramp = |dir: Direction| {
+            let ramp = self.neighbor(cell, dir)?;
+            let in_line = self
+                .links(ramp)
+                .all(|(side, _)| side == dir || side == dir.opposite());
+            (in_line && self.tunnel(ramp).is_none() && self.tunnel_under(ramp).is_none())
+                .then_some((ramp, dir.opposite()))
+        };
+        let mut sides = self.sides(cell);
+        let (first, second) = (sides.next()?, sides.next()?);
+        Some([ramp(first)?, ramp(second)?])
+    }
+}
+
+/// Settings for weave mazes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weave {
+    /// Share of the cells to try a crossing on, from 0 to 1. Crossings need
+    /// room around them, so far fewer end up in the maze.
+    pub crossings: f64,
+}
+
+impl Default for Weave {
+    fn default() -> Self {
+        Weave { crossings: 0.3 }
+    }
+}
+
+impl Weave {
+    /// Lays out crossings at random, then joins the rest with Kruskal's
+    /// algorithm, which works around the passages already there. The maze
+    /// is perfect, and the same seed always gives the same layout.
+    pub fn generate(&self, width: usize, height: usize, seed: u64) -> Maze {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut maze = Maze::new(width, height);
+        // Every crossing adds one tunnel cell at most.
+        let mut sets = DisjointSet::new(2 * maze.cell_count());
+        let mut ramps = vec![false; maze.cell_count()];
+
+        let mut cells = maze.cells().collect::<Vec<_>>();
+        cells.shuffle(&mut rng);
+        let tries = (cells.len() as f64 * self.crossings.clamp(0.0, 1.0)).round();
+        for cell in cells.into_iter().take(tries as usize) {
+            let along = if rng.gen() {
+                Direction::East
+            } else {
+                Direction::South
+            };
+            add_crossing(&mut maze, &mut sets, &mut ramps, cell, along);
+        }
+
+        // Ramps keep the passages they have.
+        let is_ramp = |cell: usize| ramps.get(cell).copied().unwrap_or(false);
+        let mut walls = maze
+            .cells()
+            .flat_map(|cell| {
+                maze.neighbors(cell)
+                    .filter(move |&(_, other)| other > cell)
+                    .map(move |(dir, other)| (cell, dir, other))
+            })
+            .filter(|&(cell, _, other)| !is_ramp(cell) && !is_ramp(other))
+            .collect::<Vec<_>>();
+        walls.shuffle(&mut rng);
+        for (cell, dir, other) in walls {
+            if sets.union(cell, other) {
+                maze.carve(cell, dir);
+            }
+        }
+        maze
+    }
+}
+
+/// Tries to put a crossing on `cell` with its tunnel running `along`,
+/// carving the bridge, its ramps and the tunnel right away. Leaves the
+/// maze untouched and returns `false` if there is no room for it or it
+/// would close a loop.
+fn add_crossing(
+    maze: &mut Maze,
+    sets: &mut DisjointSet,
+    ramps: &mut [bool],
+    cell: usize,
+    along: Direction,
+) -> bool {
+    let over = along.left();
+    let plain = |c: usize| {
+        maze.tunnel(c).is_none()
+            && maze.tunnel_under(c).is_none()
+            && !ramps.get(c).copied().unwrap_or(false)
+    };
+    let step = |c: usize, dir: Direction| maze.neighbor(c, dir);
+    let (Some(a), Some(b), Some(c), Some(d)) = (
+        step(cell, over),
+        step(cell, over.opposite()),
+        step(cell, along),
+        step(cell, along.opposite()),
+    ) else {
+        return false;
+    };
+    let (Some(before), Some(after)) = (step(a, over), step(b, over.opposite())) else {
+        return false;
+    };
+    let fresh = [cell, a, b]
+        .iter()
+        .all(|&x| maze.degree(x) == 0 && plain(x));
+    if !fresh || ![before, after, c, d].iter().all(|&x| plain(x)) {
+        return false;
+    }
+    // The bridge joins the pieces at its ends and the tunnel those at its
+    // ends; neither may join a piece to itself, and both together not the
+    // same two pieces twice.
+    let pair = |x: usize, y: usize| (x.min(y), x.max(y));
+    let bridge = pair(sets.find(before), sets.find(after));
+    let tunnel = pair(sets.find(c), sets.find(d));
+    if bridge.0 == bridge.1 || tunnel.0 == tunnel.1 || bridge == tunnel {
+        return false;
+    }
+
+    let tunnel = maze
+        .add_tunnel(cell, along)
+        .expect("the crossing has room for a tunnel");
+    for (from, dir) in [
+        (before, over.opposite()),
+        (a, over.opposite()),
+        (cell, over.opposite()),
+        (b, over.opposite()),
+        (c, along.opposite()),
+        (tunnel, along.opposite()),
+    ] {
+        let to = maze.neighbor(from, dir).expect("the crossing is inside");
+        maze.carve(from, dir);
+        sets.union(from, to);
+    }
+    ramps[a] = true;
+    ramps[b] = true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{
+        classic::{Hand, Tremaux, WallFollower},
+        solve::{distances, solve, Search},
+    };
+
+    #[test]
+    fn weave_mazes_are_perfect_and_cross() {
+        for seed in 0..8 {
+            let maze = Weave::default().generate(12, 10, seed);
+            assert!(maze.tunnel_count() > 0);
+            assert!(distances(&maze, maze.start()).iter().all(Option::is_some));
+            assert_eq!(maze.passage_count(), maze.cell_count() - 1);
+            assert_eq!(Weave::default().generate(12, 10, seed), maze);
+
+            let mut ramps = maze
+                .cells()
+                .filter_map(|cell| maze.bridge(cell))
+                .flatten()
+                .map(|(ramp, _)| ramp)
+                .collect::<Vec<_>>();
+            assert_eq!(ramps.len(), 2 * maze.tunnel_count());
+            ramps.sort_unstable();
+            ramps.dedup();
+            assert_eq!(ramps.len(), 2 * maze.tunnel_count(), "ramps are shared");
+        }
+    }
+
+    #[test]
+    fn tunnels_lead_under_crossings() {
+        use Direction::*;
+        let mut maze = Maze::new(3, 3);
+        let center = maze.cell(1, 1);
+        let tunnel = maze.add_tunnel(center, West).unwrap();
+        assert_eq!(maze.tunnel(tunnel), Some((center, East)));
+        assert_eq!(maze.tunnel_under(center), Some(tunnel));
+        assert_eq!(maze.coords(tunnel), (1, 1));
+        assert_eq!(maze.neighbor(maze.cell(0, 1), East), Some(tunnel));
+        assert_eq!(maze.neighbor(maze.cell(1, 0), South), Some(center));
+        assert_eq!(maze.neighbor(center, East), None);
+        assert_eq!(maze.add_tunnel(center, North), None);
+        assert_eq!(maze.add_tunnel(maze.cell(0, 1), East), None);
+
+        for (cell, dir) in [
+            (tunnel, East),
+            (tunnel, West),
+            (center, North),
+            (center, South),
+        ] {
+            assert!(maze.carve(cell, dir));
+        }
+        assert_eq!(maze.bridge(center), Some([(1, South), (7, North)]));
+        // There is no turning at a crossing.
+        let path = solve(&maze, maze.cell(0, 1), maze.cell(2, 1), Search::Bfs).unwrap();
+        assert_eq!(path, [3, tunnel, 5]);
+        assert_eq!(solve(&maze, 3, 1, Search::AStar), None);
+    }
+
+    #[test]
+    fn classic_solvers_go_over_and_under() {
+        let maze = Weave { crossings: 0.5 }.generate(10, 10, 3);
+        let (start, goal) = (maze.start(), maze.goal());
+        for hand in [Hand::Left, Hand::Right] {
+            assert_eq!(
+                WallFollower::new(&maze, start, goal, hand).last(),
+                Some(goal)
+            );
+        }
+        assert_eq!(Tremaux::new(&maze, start, goal).last(), Some(goal));
+    }
+}
@@ -0,0 +1,641 @@
+//! Levels drawn as images.
+//!
+//! A picture uses the same layout as [ASCII mazes](crate::maze::ascii),
+//! with a square block of pixels in place of every character: wall and
+//! floor colors for walls and passages, and marker colors for the start,
+//! the goal and items, which have to sit on cells. Blocks may be any size
+//! as long as they are all the same; by default it is worked out from the
+//! picture.
+//!
+//! Any picture can also be read as a [`Mask`] with [`import_mask`], to cut
+//! generated mazes into its shape.
+
+use std::{fmt, io::Cursor};
+
+use image::{ImageFormat, ImageOutputFormat, Rgb, RgbImage};
+
+use crate::{
+    level::{Item, MazeLevel},
+    maze::{ascii::ParseErrorKind, mask::Mask, solve, Maze, Unsupported},
+};
+
+/// Colors used for the parts of a level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub wall: [u8; 3],
+    pub floor: [u8; 3],
+    pub start: [u8; 3],
+    pub goal: [u8; 3],
+    /// Marker color for each item kind.
+    pub items: Vec<([u8; 3], String)>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            wall: [0, 0, 0],
+            floor: [255, 255, 255],
+            start: [0, 255, 0],
+            goal: [255, 0, 0],
+            items: vec![([255, 255, 0], "coin".into()), ([0, 0, 255], "key".into())],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImportOptions {
+    pub palette: Palette,
+    /// Edge length of a block in pixels; detected when `None`.
+    pub block_size: Option<u32>,
+    /// How far, as the distance between RGB values, a pixel may be from
+    /// the nearest palette color; defaults to 64.
+    pub tolerance: Option<f32>,
+}
+
+/// Settings for [`import_mask`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaskOptions {
+    /// Edge length in pixels of the square every cell covers.
+    pub cell_size: u32,
+    /// How far, as the distance between RGB values, a pixel may be from
+    /// the background color and still count as background; defaults to 64.
+    pub tolerance: Option<f32>,
+}
+
+impl Default for MaskOptions {
+    fn default() -> Self {
+        MaskOptions {
+            cell_size: 8,
+            tolerance: None,
+        }
+    }
+}
+
+/// Something odd about an imported level that does not stop it loading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportWarning {
+    /// There is no start marker; the first cell is used.
+    NoStart,
+    /// There is no goal marker; the last cell is used.
+    NoGoal,
+    /// The goal cannot be reached from the start.
+    GoalUnreachable,
+    /// An item cannot be reached from the start.
+    ItemUnreachable { kind: String, cell: (usize, usize) },
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportWarning::NoStart => write!(f, "no start marker, using the first cell"),
+            ImportWarning::NoGoal => write!(f, "no goal marker, using the last cell"),
+            ImportWarning::GoalUnreachable => write!(f, "the goal cannot be reached"),
+            ImportWarning::ItemUnreachable { kind, cell } => {
+                write!(f, "{kind} at {cell:?} cannot be reached")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Edge length of a block in pixels.
+    pub block_size: u32,
+    pub warnings: Vec<ImportWarning>,
+}
+
+/// Why a picture could not be imported or exported. Positions are in pixels for
+/// [`UnknownColor`](PictureError::UnknownColor) and in blocks otherwise,
+/// counting from the top left.
+#[derive(Debug)]
+pub enum PictureError {
+    /// The data is not a readable PNG.
+    Decode(image::ImageError),
+    /// The picture cannot be divided into blocks of the given size.
+    BlockSize {
+        width: u32,
+        height: u32,
+        block_size: u32,
+    },
+    /// A pixel is not close to any palette color.
+    UnknownColor { x: u32, y: u32, color: [u8; 3] },
+    /// An item marker is placed where a wall goes.
+    ItemOnWall { x: usize, y: usize },
+    /// The blocks do not form a valid maze.
+    Layout {
+        x: usize,
+        y: usize,
+        kind: ParseErrorKind,
+    },
+    /// The level cannot be drawn as a picture.
+    Unsupported(Unsupported),
+    /// Blocks of the given size are empty or make the picture too large.
+    ExportBlockSize { block_size: u32 },
+}
+
+impl fmt::Display for PictureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PictureError::Decode(e) => write!(f, "could not decode picture: {e}"),
+            PictureError::BlockSize {
+                width,
+                height,
+                block_size,
+            } => write!(
+                f,
+                "a {width}x{height} picture cannot be split into {block_size}-pixel blocks"
+            ),
+            PictureError::UnknownColor { x, y, color } => {
+                write!(f, "pixel ({x}, {y}) has unknown color {color:?}")
+            }
+            PictureError::ItemOnWall { x, y } => {
+                write!(f, "block ({x}, {y}): items must be on a cell")
+            }
+            PictureError::Layout { x, y, kind } => write!(f, "block ({x}, {y}): {kind}"),
+            PictureError::Unsupported(e) => write!(f, "cannot draw the level: {e}"),
+            PictureError::ExportBlockSize { block_size } => {
+                write!(f, "cannot draw the level in {block_size}-pixel blocks")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PictureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PictureError::Decode(e) => Some(e),
+            PictureError::Unsupported(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for PictureError {
+    fn from(e: image::ImageError) -> Self {
+        PictureError::Decode(e)
+    }
+}
+
+impl From<Unsupported> for PictureError {
+    fn from(e: Unsupported) -> Self {
+        PictureError::Unsupported(e)
+    }
+}
+
+/// What a pixel stands for, by its nearest palette color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Block {
+    Wall,
+    Floor,
+    Start,
+    Goal,
+    Item(usize),
+}
+
+/// Reads a level from PNG data.
+pub fn import(
+    png: &[u8],
+    options: &ImportOptions,
+) -> Result<(MazeLevel, ImportReport), PictureError> {
+    let picture = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+    let (width, height) = picture.dimensions();
+    let palette = &options.palette;
+    let tolerance = options.tolerance.unwrap_or(64.0);
+
+    let mut colors = vec![
+        (palette.wall, Block::Wall),
+        (palette.floor, Block::Floor),
+        (palette.start, Block::Start),
+        (palette.goal, Block::Goal),
+    ];
+    colors.extend(
+        palette
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, &(color, _))| (color, Block::Item(i))),
+    );
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for (x, y, pixel) in picture.enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        // Transparent areas count as floor.
+        if a < 128 {
+            pixels.push(Block::Floor);
+            continue;
+        }
+        let distance = |c: [u8; 3]| color_distance([r, g, b], c);
+        let (color, block) = colors
+            .iter()
+            .min_by(|a, b| distance(a.0).total_cmp(&distance(b.0)))
+            .expect("the palette is never empty");
+        if distance(*color) > tolerance {
+            return Err(PictureError::UnknownColor {
+                x,
+                y,
+                color: [r, g, b],
+            });
+        }
+        pixels.push(*block);
+    }
+    let block_at = |x: u32, y: u32| pixels[(y * width + x) as usize];
+
+    let block_size = match options.block_size {
+        Some(size) => size,
+        None => detect_block_size(width, height, &block_at),
+    };
+    if block_size == 0 || width % block_size != 0 || height % block_size != 0 {
+        return Err(PictureError::BlockSize {
+            width,
+            height,
+            block_size,
+        });
+    }
+
+    // Read each block from its middle pixel and hand the grid to the
+    // ASCII parser, which knows the layout rules.
+    let (columns, rows) = (
+        (width / block_size) as usize,
+        (height / block_size) as usize,
+    );
+    let mut text = String::with_capacity((columns + 1) * rows);
+    let mut items = Vec::new();
+    let (mut has_start, mut has_goal) = (false, false);
+    for y in 0..rows {
+        for x in 0..columns {
+            let middle = |i: usize| i as u32 * block_size + block_size / 2;
+            text.push(match block_at(middle(x), middle(y)) {
+                Block::Wall => '#',
+                Block::Floor => '.',
+                Block::Start => {
+                    has_start = true;
+                    'S'
+                }
+                Block::Goal => {
+                    has_goal = true;
+                    'G'
+                }
+                Block::Item(i) => {
+                    if x % 2 == 0 || y % 2 == 0 {
+                        return Err(PictureError::ItemOnWall { x, y });
+                    }
+                    items.push((palette.items[i].1.clone(), (x / 2, y / 2)));
+                    '.'
+                }
+            });
+        }
+        text.push('\n');
+    }
+    let maze = Maze::from_ascii(&text).map_err(|e| PictureError::Layout {
+        x: e.column - 1,
+        y: e.line - 1,
+        kind: e.kind,
+    })?;
+
+    let mut report = ImportReport {
+        block_size,
+        warnings: Vec::new(),
+    };
+    if !has_start {
+        report.warnings.push(ImportWarning::NoStart);
+    }
+    if !has_goal {
+        report.warnings.push(ImportWarning::NoGoal);
+    }
+    let distances = solve::distances(&maze, maze.start());
+    if distances[maze.goal()].is_none() {
+        report.warnings.push(ImportWarning::GoalUnreachable);
+    }
+    let mut level = MazeLevel::new(maze);
+    for (kind, (x, y)) in items {
+        let cell = level.maze.cell(x, y);
+        if distances[cell].is_none() {
+            report.warnings.push(ImportWarning::ItemUnreachable {
+                kind: kind.clone(),
+                cell: (x, y),
+            });
+        }
+        level.items.push(Item { kind, cell });
+    }
+    Ok((level, report))
+}
+
+/// Reads a mask from PNG data, such as a logo or lettering. The top-left
+/// pixel has the background color, and a cell belongs to the maze when
+/// more than half of the pixels it covers differ from it. All fully
+/// transparent pixels count as the same color, so shapes on a transparent
+/// background work as well. The cells along the right and bottom edges
+/// cover whatever is left of the picture.
+pub fn import_mask(png: &[u8], options: &MaskOptions) -> Result<Mask, PictureError> {
+    let picture = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+    let (width, height) = picture.dimensions();
+    let size = options.cell_size;
+    if size == 0 {
+        return Err(PictureError::BlockSize {
+            width,
+            height,
+            block_size: size,
+        });
+    }
+    let tolerance = options.tolerance.unwrap_or(64.0);
+    let [br, bg, bb, ba] = picture.get_pixel(0, 0).0;
+    let in_shape = |[r, g, b, a]: [u8; 4]| match (a < 128, ba < 128) {
+        (true, true) => false,
+        (false, false) => color_distance([r, g, b], [br, bg, bb]) > tolerance,
+        _ => true,
+    };
+
+    let (columns, rows) = (width.div_ceil(size), height.div_ceil(size));
+    // Pixels in the shape and in total for every cell.
+    let mut counts = vec![(0, 0); (columns * rows) as usize];
+    for (x, y, pixel) in picture.enumerate_pixels() {
+        let (shape, total) = &mut counts[(y / size * columns + x / size) as usize];
+        *shape += u32::from(in_shape(pixel.0));
+        *total += 1;
+    }
+    let mut mask = Mask::new(columns as usize, rows as usize);
+    for (i, &(shape, total)) in counts.iter().enumerate() {
+        mask.set(
+            i % columns as usize,
+            i / columns as usize,
+            2 * shape > total,
+        );
+    }
+    Ok(mask)
+}
+
+/// Distance between two colors as points in RGB space.
+fn color_distance(a: [u8; 3], b: [u8; 3]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| (f32::from(a) - f32::from(b)).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// The largest block size every run of same-colored pixels fits, which is
+/// the block size of a picture drawn on a grid.
+fn detect_block_size(width: u32, height: u32, block_at: &impl Fn(u32, u32) -> Block) -> u32 {
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    let mut size = gcd(width, height);
+    let mut scan = |len: u32, lines: u32, at: &dyn Fn(u32, u32) -> Block| {
+        for line in 0..lines {
+            let mut run = 1;
+            for i in 1..len {
+                if at(i, line) == at(i - 1, line) {
+                    run += 1;
+                } else {
+                    size = gcd(size, run);
+                    run = 1;
+                }
+            }
+        }
+    };
+    scan(width, height, &|i, line| block_at(i, line));
+    scan(height, width, &|i, line| block_at(line, i));
+    size
+}
+
+/// Draws `level` as a PNG with `block_size` pixels per block. Items whose
+/// kind has no palette color are left out. Only plain square mazes can be
+/// drawn, see [`Maze::check_plain`].
+pub fn export(
+    level: &MazeLevel,
+    block_size: u32,
+    palette: &Palette,
+) -> Result<Vec<u8>, PictureError> {
+    let maze = &level.maze;
+    let text = maze.to_ascii()?;
+    let rows = text.lines().map(str::as_bytes).collect::<Vec<_>>();
+    let side = |blocks: usize| {
+        u32::try_from(blocks)
+            .ok()
+            .and_then(|blocks| blocks.checked_mul(block_size))
+            .filter(|_| block_size > 0)
+            .ok_or(PictureError::ExportBlockSize { block_size })
+    };
+    let (width, height) = (side(rows[0].len())?, side(rows.len())?);
+
+    let item_color = |x: usize, y: usize| {
+        let cell = maze.cell(x, y);
+        level.items.iter().rev().find_map(|item| {
+            let (color, _) = palette.items.iter().find(|(_, kind)| *kind == item.kind)?;
+            (item.cell == cell).then_some(*color)
+        })
+    };
+    let picture = RgbImage::from_fn(width, height, |px, py| {
+        let (x, y) = ((px / block_size) as usize, (py / block_size) as usize);
+        let color = match rows[y][x] {
+            b'#' => palette.wall,
+            b'S' => palette.start,
+            b'G' => palette.goal,
+            _ if x % 2 == 1 && y % 2 == 1 => item_color(x / 2, y / 2).unwrap_or(palette.floor),
+            _ => palette.floor,
+        };
+        Rgb(color)
+    });
+    let mut png = Vec::new();
+    picture
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .expect("encoding into memory cannot fail");
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate, mask::Regions, Topology};
+
+    fn picture(rows: &[&str], block_size: u32) -> Vec<u8> {
+        let palette = Palette::default();
+        let picture = RgbImage::from_fn(
+            rows[0].len() as u32 * block_size,
+            rows.len() as u32 * block_size,
+            |x, y| {
+                let c = rows[(y / block_size) as usize].as_bytes()[(x / block_size) as usize];
+                Rgb(match c {
+                    b'#' => palette.wall,
+                    b'S' => palette.start,
+                    b'G' => palette.goal,
+                    b'c' => palette.items[0].0,
+                    b'?' => [128, 0, 128],
+                    _ => palette.floor,
+                })
+            },
+        );
+        let mut png = Vec::new();
+        picture
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn round_trips_generated_levels() {
+        let palette = Palette::default();
+        for block_size in [1, 4] {
+            let mut level = MazeLevel::new(generate::recursive_backtracker(9, 7, 2));
+            level.maze.set_goal(level.maze.cell(4, 3));
+            level.items.push(Item {
+                kind: "key".into(),
+                cell: level.maze.cell(8, 0),
+            });
+            let png = export(&level, block_size, &palette).unwrap();
+            let (imported, report) = import(&png, &ImportOptions::default()).unwrap();
+            assert_eq!(imported, level);
+            assert_eq!(
+                report,
+                ImportReport {
+                    block_size,
+                    warnings: vec![]
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn reports_unreachable_goal_and_items() {
+        let png = picture(&["#######", "#S..#G#", "###.###", "#c#...#", "#######"], 3);
+        let (level, report) = import(&png, &ImportOptions::default()).unwrap();
+        assert_eq!(level.items[0].cell, level.maze.cell(0, 1));
+        assert_eq!(report.block_size, 3);
+        assert_eq!(
+            report.warnings,
+            [
+                ImportWarning::GoalUnreachable,
+                ImportWarning::ItemUnreachable {
+                    kind: "coin".into(),
+                    cell: (0, 1)
+                }
+            ]
+        );
+
+        let png = picture(&["#####", "#...#", "#####"], 2);
+        let (_, report) = import(&png, &ImportOptions::default()).unwrap();
+        assert_eq!(
+            report.warnings,
+            [ImportWarning::NoStart, ImportWarning::NoGoal]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_pictures() {
+        let options = ImportOptions::default();
+        assert!(matches!(
+            import(b"not a png", &options),
+            Err(PictureError::Decode(_))
+        ));
+        assert!(matches!(
+            import(&picture(&["###", "#?#", "###"], 2), &options),
+            Err(PictureError::UnknownColor { x: 2, y: 2, .. })
+        ));
+        assert!(matches!(
+            import(
+                &picture(&["#####", "#S.G#", "#####"], 1),
+                &ImportOptions {
+                    block_size: Some(2),
+                    ..Default::default()
+                }
+            ),
+            Err(PictureError::BlockSize { block_size: 2, .. })
+        ));
+        assert!(matches!(
+            import(&picture(&["#####", "#Sc.#", "#####"], 1), &options),
+            Err(PictureError::ItemOnWall { x: 2, y: 1 })
+        ));
+        assert!(matches!(
+            import(
+                &picture(&["#####", "#S#.#", "#...#", "#.#.#", "#####"], 1),
+                &options
+            ),
+            Err(PictureError::Layout {
+                x: 2,
+                y: 2,
+                kind: ParseErrorKind::OpenCorner
+            })
+        ));
+    }
+
+    #[test]
+    fn only_plain_levels_are_exported() {
+        let palette = Palette::default();
+        for (source, plain) in [
+            (include_str!("../assets/levels/tutorial.maze"), true),
+            (include_str!("../assets/levels/honeycomb.maze"), false),
+            (include_str!("../assets/levels/tower.maze"), false),
+        ] {
+            let level = MazeLevel::from_json(source).unwrap();
+            match export(&level, 2, &palette) {
+                Ok(_) => assert!(plain),
+                Err(PictureError::Unsupported(e)) => {
+                    assert!(!plain);
+                    assert_eq!(level.maze.check_plain(), Err(e));
+                    assert_eq!(level.maze.encode(), Err(e));
+                }
+                Err(e) => panic!("unexpected error {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_block_sizes_that_cannot_be_drawn() {
+        let level = MazeLevel::new(generate::recursive_backtracker(3, 2, 1));
+        for block_size in [0, u32::MAX / 4] {
+            assert!(matches!(
+                export(&level, block_size, &Palette::default()),
+                Err(PictureError::ExportBlockSize { block_size: b }) if b == block_size
+            ));
+        }
+    }
+
+    #[test]
+    fn masks_follow_the_shape() {
+        let png = picture(&["......", ".##.#.", "..##.#"], 4);
+        let expected = Mask::from_text("......\n.##.#.\n..##.#").unwrap();
+        let options = MaskOptions {
+            cell_size: 4,
+            ..Default::default()
+        };
+        assert_eq!(import_mask(&png, &options).unwrap(), expected);
+        // Cells covering more or less than the picture count what they
+        // cover.
+        let options = MaskOptions {
+            cell_size: 8,
+            ..Default::default()
+        };
+        let expected = Mask::from_text("...\n.#.").unwrap();
+        assert_eq!(import_mask(&png, &options).unwrap(), expected);
+        assert!(matches!(
+            import_mask(
+                &png,
+                &MaskOptions {
+                    cell_size: 0,
+                    ..Default::default()
+                }
+            ),
+            Err(PictureError::BlockSize { .. })
+        ));
+
+        // The bird is drawn on a transparent background.
+        let bird = include_bytes!("../assets/branding/bevy_bird_dark.png");
+        let mask = import_mask(bird, &MaskOptions::default()).unwrap();
+        assert_eq!((mask.width(), mask.height()), (32, 32));
+        assert!(!mask.is_enabled(0, 0) && mask.is_enabled(16, 16));
+        let maze = mask
+            .generate(&generate::Kruskal, Topology::Square, 1, Regions::Bridge)
+            .unwrap();
+        assert!(maze.hole_count() > 32 * 32 / 2);
+        assert_eq!(
+            solve::distances(&maze, maze.start())
+                .iter()
+                .filter(|d| d.is_some())
+                .count(),
+            32 * 32 - maze.hole_count()
+        );
+    }
+}
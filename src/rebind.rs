@@ -0,0 +1,246 @@
+//! The controls panel, which lists every action with its keys and buttons
+//! and lets the player bind another one by clicking an action and pressing
+//! a key or gamepad button. Changes are saved to [`BINDINGS_FILE`] right
+//! away. Escape or a click outside the panel backs out of a rebinding.
+
+use bevy::prelude::*;
+
+use crate::action::{Action, Bindings, BINDINGS_FILE};
+
+/// The action waiting for a key or button to be bound to it, if any.
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding(pub Option<Action>);
+
+/// The controls panel, holding a row for every action with its label and
+/// a [`BindingButton`] for its keys and buttons.
+#[derive(Component)]
+pub struct ControlsPanel;
+
+/// The button showing the keys and buttons bound to an action.
+#[derive(Component)]
+pub struct BindingButton(pub Action);
+
+const IDLE: Color = Color::rgba(0.15, 0.15, 0.15, 0.8);
+const WAITING: Color = Color::rgba(0.35, 0.25, 0.1, 0.9);
+
+pub fn setup_controls_panel(mut commands: Commands) {
+    let text_style = TextStyle {
+        font_size: 18.0,
+        color: Color::WHITE,
+        ..default()
+    };
+    commands
+        .spawn((
+            ControlsPanel,
+            // Tells clicks on the panel from clicks outside it.
+            Interaction::default(),
+            NodeBundle {
+                background_color: BackgroundColor(Color::BLACK.with_a(0.7)),
+                z_index: ZIndex::Global(i32::MAX - 1),
+                visibility: Visibility::Hidden,
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(30.0),
+                    top: Val::Percent(15.0),
+                    width: Val::Percent(40.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.0)),
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                "Controls: click an action, then press a key or button",
+                text_style.clone(),
+            ));
+            for action in Action::ALL {
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::SpaceBetween,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(TextBundle::from_section(action.label(), text_style.clone()));
+                        row.spawn((
+                            BindingButton(action),
+                            ButtonBundle {
+                                background_color: BackgroundColor(IDLE),
+                                style: Style {
+                                    min_width: Val::Px(140.0),
+                                    padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                        ))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section("", text_style.clone()));
+                        });
+                    });
+            }
+        });
+}
+
+/// Shows or hides the panel, dropping a rebinding left unfinished.
+pub fn toggle_controls_panel(
+    mut panel: Query<&mut Visibility, With<ControlsPanel>>,
+    mut rebinding: ResMut<Rebinding>,
+    actions: Res<Input<Action>>,
+) {
+    if actions.just_pressed(Action::Controls) {
+        let mut vis = panel.single_mut();
+        *vis = match *vis {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+        rebinding.0 = None;
+    }
+}
+
+/// Starts rebinding the action whose button is clicked, or stops when it
+/// is clicked again.
+pub fn click_bindings(
+    buttons: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            rebinding.0 = match rebinding.0 {
+                Some(action) if action == button.0 => None,
+                _ => Some(button.0),
+            };
+        }
+    }
+}
+
+/// Binds the next key or gamepad button pressed to the action waiting for
+/// one and saves the bindings. The key or button is then swallowed, so
+/// that it does not also trigger what it is now bound to. Escape or a click
+/// outside the panel cancels instead, leaving the bindings as they are.
+pub fn capture_binding(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    mouse: Res<Input<MouseButton>>,
+    panel: Query<&Interaction, Or<(With<ControlsPanel>, With<BindingButton>)>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let outside = panel.iter().all(|&i| i == Interaction::None);
+    if keys.just_pressed(KeyCode::Escape) {
+        keys.reset(KeyCode::Escape);
+        rebinding.0 = None;
+        return;
+    }
+    if outside && mouse.get_just_pressed().next().is_some() {
+        rebinding.0 = None;
+        return;
+    }
+    let key = keys.get_just_pressed().next().copied();
+    let button = buttons.get_just_pressed().next().copied();
+    if let Some(key) = key {
+        keys.reset(key);
+        bindings.rebind(action, key);
+    } else if let Some(button) = button {
+        buttons.reset(button);
+        bindings.rebind_button(action, button.button_type);
+    } else {
+        return;
+    }
+    rebinding.0 = None;
+    if let Err(e) = bindings.save(BINDINGS_FILE) {
+        warn!("could not save the controls to {BINDINGS_FILE}: {e}");
+    }
+}
+
+/// Shows the keys and buttons bound to every action on its button.
+pub fn update_binding_labels(
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+    mut buttons: Query<(&BindingButton, &Children, &mut BackgroundColor)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    for (button, children, mut color) in &mut buttons {
+        let waiting = rebinding.0 == Some(button.0);
+        let label = if waiting {
+            "press a key or button".to_owned()
+        } else {
+            let names = bindings.names(button.0);
+            if names.is_empty() {
+                "unbound".to_owned()
+            } else {
+                names.join(", ")
+            }
+        };
+        *color = BackgroundColor(if waiting { WAITING } else { IDLE });
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Input<MouseButton>>()
+            .insert_resource(Rebinding(Some(Action::Jump)))
+            .init_resource::<Bindings>()
+            .add_systems(Update, capture_binding);
+        app
+    }
+
+    #[test]
+    fn escape_and_clicks_outside_cancel() {
+        let mut app = app();
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Escape);
+        app.update();
+        assert_eq!(app.world.resource::<Rebinding>().0, None);
+        assert_eq!(*app.world.resource::<Bindings>(), Bindings::default());
+        // Swallowed, so that it does not free the cursor as well.
+        assert!(!app
+            .world
+            .resource::<Input<KeyCode>>()
+            .pressed(KeyCode::Escape));
+
+        app.world.resource_mut::<Rebinding>().0 = Some(Action::Jump);
+        let panel = app.world.spawn((ControlsPanel, Interaction::None)).id();
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .press(MouseButton::Left);
+        app.update();
+        assert_eq!(app.world.resource::<Rebinding>().0, None);
+        assert_eq!(*app.world.resource::<Bindings>(), Bindings::default());
+
+        // A click on the panel itself keeps waiting.
+        app.world.resource_mut::<Rebinding>().0 = Some(Action::Jump);
+        app.world.entity_mut(panel).insert(Interaction::Hovered);
+        let mut mouse = app.world.resource_mut::<Input<MouseButton>>();
+        mouse.release(MouseButton::Left);
+        mouse.clear();
+        mouse.press(MouseButton::Left);
+        app.update();
+        assert_eq!(app.world.resource::<Rebinding>().0, Some(Action::Jump));
+    }
+}
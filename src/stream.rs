@@ -0,0 +1,190 @@
+//! Streaming of endless mazes around the ball.
+//!
+//! The maze is made of [chunks](crate::maze::chunks) generated from the
+//! seed and their coordinates. Chunks within [`ChunkStreaming::radius`]
+//! of the ball's chunk are spawned with their colliders, and despawned
+//! again once they are more than one chunk further away, so that rolling
+//! back and forth across a border does not spawn the same chunks over and
+//! over.
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_xpbd_3d::components::RigidBody;
+
+use crate::{
+    maze::{
+        chunks::{Chunk, Chunks},
+        generate,
+    },
+    output::ExampleDisplay,
+    world::{boxes_collider, boxes_mesh, MazeLayout, MazeMaterials},
+};
+
+/// Turns on endless mode while present; [`CurrentMaze`] is not spawned
+/// then.
+///
+/// [`CurrentMaze`]: crate::world::CurrentMaze
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkStreaming {
+    pub chunks: Chunks,
+    /// The generator carving every chunk, by its name for
+    /// [`generate::by_name`].
+    pub generator: String,
+    /// Chunks kept around the ball's chunk in every direction.
+    pub radius: u32,
+}
+
+impl Default for ChunkStreaming {
+    /// Chunks of 16 by 16 cells from a random seed, which is logged so the
+    /// maze can be reported and reproduced.
+    fn default() -> Self {
+        let seed = rand::random();
+        info!("endless maze seed: {seed}");
+        ChunkStreaming {
+            chunks: Chunks { seed, size: 16 },
+            generator: "backtracker".into(),
+            radius: 1,
+        }
+    }
+}
+
+impl ChunkStreaming {
+    /// Column and row of the chunk that `position` is over.
+    pub fn chunk_at(&self, layout: &MazeLayout, position: Vec3) -> (i32, i32) {
+        let size = self.chunks.size as f32 * layout.cell_size;
+        (
+            (position.x / size).floor() as i32,
+            (position.z / size).floor() as i32,
+        )
+    }
+
+    /// World position of the north-west corner of a chunk, on the floor
+    /// surface.
+    pub fn chunk_origin(&self, layout: &MazeLayout, (x, y): (i32, i32)) -> Vec3 {
+        let size = self.chunks.size as f32 * layout.cell_size;
+        Vec3::new(x as f32 * size, 0.0, y as f32 * size)
+    }
+}
+
+/// A spawned chunk of an endless maze, by its column and row.
+#[derive(Component)]
+pub struct StreamedChunk(pub (i32, i32));
+
+/// Spawns the chunks around the ball that are missing and despawns those
+/// that are too far away, see the [module docs](self).
+pub fn stream_chunks(
+    mut commands: Commands,
+    ball: Query<&Transform, With<ExampleDisplay>>,
+    spawned: Query<(Entity, &StreamedChunk)>,
+    streaming: Res<ChunkStreaming>,
+    layout: Res<MazeLayout>,
+    materials: Res<MazeMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok(ball) = ball.get_single() else {
+        return;
+    };
+    let (x, y) = streaming.chunk_at(&layout, ball.translation);
+    let radius = streaming.radius as i32;
+    let mut present = HashSet::new();
+    for (entity, chunk) in &spawned {
+        let (cx, cy) = chunk.0;
+        if (cx - x).abs().max((cy - y).abs()) > radius + 1 {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            present.insert(chunk.0);
+        }
+    }
+
+    let Some(generator) = generate::by_name(&streaming.generator) else {
+        warn!("unknown chunk generator {:?}", streaming.generator);
+        return;
+    };
+    for cy in y - radius..=y + radius {
+        for cx in x - radius..=x + radius {
+            if present.contains(&(cx, cy)) {
+                continue;
+            }
+            let chunk = streaming.chunks.chunk(generator.as_ref(), (cx, cy));
+            let (floor, walls) = chunk_boxes(&layout, &chunk);
+            let mut solid = walls.clone();
+            solid.push(floor);
+            commands
+                .spawn((
+                    StreamedChunk(chunk.coord),
+                    RigidBody::Static,
+                    boxes_collider(&solid),
+                    SpatialBundle::from_transform(Transform::from_translation(
+                        streaming.chunk_origin(&layout, chunk.coord),
+                    )),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(boxes_mesh(&[floor])),
+                        material: materials.tile.clone(),
+                        ..default()
+                    });
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(boxes_mesh(&walls)),
+                        material: materials.wall.clone(),
+                        ..default()
+                    });
+                });
+        }
+    }
+}
+
+/// The floor slab and the wall runs of `chunk` as sizes and centers,
+/// relative to its north-west corner.
+fn chunk_boxes(layout: &MazeLayout, chunk: &Chunk) -> ((Vec3, Vec3), Vec<(Vec3, Vec3)>) {
+    let extent = layout.extent(&chunk.maze);
+    let floor = (
+        Vec3::new(extent.x, layout.floor_thickness, extent.y),
+        Vec3::new(
+            extent.x / 2.0,
+            layout.floor_y - layout.floor_thickness / 2.0,
+            extent.y / 2.0,
+        ),
+    );
+    let walls = chunk
+        .wall_runs()
+        .iter()
+        .map(|run| layout.wall_run(run))
+        .collect();
+    (floor, walls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generate::RecursiveBacktracker;
+
+    #[test]
+    fn chunks_line_up_with_positions() {
+        let layout = MazeLayout::default();
+        let streaming = ChunkStreaming {
+            chunks: Chunks { seed: 3, size: 5 },
+            generator: "prim".into(),
+            radius: 1,
+        };
+        for coord in [(0, 0), (2, -1), (-4, -7)] {
+            let origin = streaming.chunk_origin(&layout, coord);
+            let middle = origin + Vec3::new(2.5, 0.0, 2.5) * layout.cell_size;
+            assert_eq!(streaming.chunk_at(&layout, middle), coord);
+        }
+
+        // The walls of a chunk stay within its area, but for the halves
+        // of the wall thickness reaching over the north and west borders.
+        let chunk = streaming.chunks.chunk(&RecursiveBacktracker, (1, 1));
+        let (floor, walls) = chunk_boxes(&layout, &chunk);
+        let reach = layout.wall_thickness / 2.0 + 1e-4;
+        for (size, center) in walls {
+            let (min, max) = (center - size / 2.0, center + size / 2.0);
+            assert!(min.x >= -reach && min.z >= -reach, "{min}");
+            assert!(
+                max.x <= floor.0.x + reach && max.z <= floor.0.z + reach,
+                "{max}"
+            );
+            assert!(max.x < floor.0.x - reach || max.z < floor.0.z - reach);
+        }
+    }
+}
@@ -0,0 +1,1324 @@
+use std::{
+    collections::BTreeMap,
+    f32::consts::{PI, TAU},
+    ops::Range,
+};
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+use bevy_xpbd_3d::components::{Collider, CollidingEntities, RigidBody, Sensor};
+
+use crate::maze::{generate, solve, wall_runs, Direction, Maze, Topology, WallRun};
+
+/// The maze the world is built from.
+#[derive(Resource)]
+pub struct CurrentMaze(pub Maze);
+
+impl Default for CurrentMaze {
+    /// A freshly generated maze with the goal as far from the start as
+    /// possible; its seed is logged so the layout can be reported and
+    /// reproduced.
+    fn default() -> Self {
+        let seed = rand::random();
+        info!("maze seed: {seed}");
+        let mut maze = generate::recursive_backtracker(8, 8, seed);
+        maze.set_goal(solve::farthest(&maze, maze.start()).0);
+        CurrentMaze(maze)
+    }
+}
+
+/// Dimensions of the spawned maze geometry, in world units.
+#[derive(Resource, Clone, Debug)]
+pub struct MazeLayout {
+    /// Edge length of one cell, measured between wall centers.
+    pub cell_size: f32,
+    pub wall_height: f32,
+    pub wall_thickness: f32,
+    /// Height of the floor surface.
+    pub floor_y: f32,
+    pub floor_thickness: f32,
+}
+
+impl Default for MazeLayout {
+    fn default() -> Self {
+        MazeLayout {
+            cell_size: 2.0,
+            wall_height: 1.0,
+            wall_thickness: 0.2,
+            floor_y: -1.0,
+            floor_thickness: 0.1,
+        }
+    }
+}
+
+impl MazeLayout {
+    /// Circumradius of a hex cell; hex cells are `cell_size` across flats.
+    pub fn hex_radius(&self) -> f32 {
+        self.cell_size / 3f32.sqrt()
+    }
+
+    /// Height between the surfaces of two floors stacked on each other.
+    /// Every floor rests on the walls of the one below.
+    pub fn floor_spacing(&self) -> f32 {
+        self.wall_height + self.floor_thickness
+    }
+
+    /// The floor of `maze` a point at height `y` is on.
+    pub fn floor_at(&self, maze: &Maze, y: f32) -> usize {
+        let floor = ((y - self.floor_y) / self.floor_spacing()).floor();
+        floor.clamp(0.0, (maze.floors() - 1) as f32) as usize
+    }
+
+    /// World position of the middle of `cell`, on the floor surface. That
+    /// of a crossing is on its bridge.
+    pub fn cell_center(&self, maze: &Maze, cell: usize) -> Vec3 {
+        let (x, y) = maze.coords(cell);
+        let (x, z) = match maze.topology() {
+            Topology::Square => (
+                (x as f32 + 0.5) * self.cell_size,
+                (y as f32 + 0.5) * self.cell_size,
+            ),
+            Topology::Hex => (
+                (1.0 + 1.5 * x as f32) * self.hex_radius(),
+                (y as f32 + 0.5 + 0.5 * (x % 2) as f32) * self.cell_size,
+            ),
+            Topology::Polar if y == 0 => return self.polar_origin(maze),
+            Topology::Polar => {
+                let (from, to) = self.polar_span(maze, cell);
+                let radial = radial((from + to) / 2.0) * (y as f32 + 0.5) * self.cell_size;
+                return self.polar_origin(maze) + radial;
+            }
+        };
+        let mut y = self.floor_y + maze.floor(cell) as f32 * self.floor_spacing();
+        if maze.tunnel_under(cell).is_some() {
+            y += self.floor_spacing();
+        }
+        Vec3::new(x, y, z)
+    }
+
+    /// Middle of a polar maze, on the floor surface. The center cell is
+    /// `cell_size` in radius and every ring `cell_size` deep.
+    fn polar_origin(&self, maze: &Maze) -> Vec3 {
+        let radius = maze.height() as f32 * self.cell_size;
+        Vec3::new(radius, self.floor_y, radius)
+    }
+
+    /// Angles bounding a cell of a polar maze, see [`WallShape::Arc`].
+    fn polar_span(&self, maze: &Maze, cell: usize) -> (f32, f32) {
+        let (x, ring) = maze.coords(cell);
+        let step = TAU / maze.row_len(ring) as f32;
+        (x as f32 * step, (x + 1) as f32 * step)
+    }
+
+    /// Size of the floor area spanned by `maze`, along X and Z. The maze
+    /// starts at the origin.
+    pub fn extent(&self, maze: &Maze) -> Vec2 {
+        let (width, height) = (maze.width() as f32, maze.height() as f32);
+        match maze.topology() {
+            Topology::Square => Vec2::new(width, height) * self.cell_size,
+            Topology::Hex => {
+                let shift = if maze.width() > 1 { 0.5 } else { 0.0 };
+                Vec2::new(
+                    (1.5 * width + 0.5) * self.hex_radius(),
+                    (height + shift) * self.cell_size,
+                )
+            }
+            Topology::Polar => Vec2::splat(2.0 * height * self.cell_size),
+        }
+    }
+
+    /// The cell whose floor area contains `position`. Only mazes with
+    /// several floors look at its height, see [`floor_at`](Self::floor_at),
+    /// and weave mazes to tell a bridge from the tunnel below. Holes of
+    /// masked mazes have no floor area.
+    pub fn cell_at(&self, maze: &Maze, position: Vec3) -> Option<usize> {
+        match maze.topology() {
+            Topology::Square => {
+                let x = (position.x / self.cell_size).floor();
+                let y = (position.z / self.cell_size).floor();
+                if x < 0.0 || y < 0.0 {
+                    return None;
+                }
+                let (x, y) = (x as usize, y as usize);
+                if x >= maze.width() || y >= maze.height() {
+                    return None;
+                }
+                let cell = maze.cell_on(self.floor_at(maze, position.y), x, y);
+                if !maze.is_enabled(cell) {
+                    return None;
+                }
+                match maze.tunnel_under(cell) {
+                    Some(tunnel) if position.y < self.floor_y + self.wall_height => Some(tunnel),
+                    _ => Some(cell),
+                }
+            }
+            Topology::Hex => {
+                // A hex cell is the area closer to its center than to any
+                // other, so check the centers around the rough position.
+                let column = (position.x / (1.5 * self.hex_radius())).floor() as isize;
+                let row = (position.z / self.cell_size).floor() as isize;
+                let (cell, distance) = (column - 1..=column + 1)
+                    .flat_map(|x| (row - 1..=row + 1).map(move |y| (x, y)))
+                    .filter_map(|(x, y)| {
+                        let x = usize::try_from(x).ok().filter(|&x| x < maze.width())?;
+                        let y = usize::try_from(y).ok().filter(|&y| y < maze.height())?;
+                        let cell = maze.cell(x, y);
+                        let offset = self.cell_center(maze, cell) - position;
+                        Some((cell, offset.xz().length()))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))?;
+                // Outside the maze the nearest center can be far away.
+                (distance <= self.hex_radius() && maze.is_enabled(cell)).then_some(cell)
+            }
+            Topology::Polar => {
+                let offset = (position - self.polar_origin(maze)).xz();
+                let ring = (offset.length() / self.cell_size) as usize;
+                if ring >= maze.height() {
+                    return None;
+                }
+                let len = maze.row_len(ring);
+                let turn = offset.y.atan2(offset.x).rem_euclid(TAU) / TAU;
+                Some(maze.cell(((turn * len as f32) as usize).min(len - 1), ring))
+            }
+        }
+    }
+
+    /// Size and placement of the box making up the wall on the `dir` side
+    /// of `cell`.
+    ///
+    /// Panics for the curved sides of polar cells; [`wall_shape`] covers
+    /// every side.
+    ///
+    /// [`wall_shape`]: Self::wall_shape
+    pub fn wall(&self, maze: &Maze, cell: usize, dir: Direction) -> (Vec3, Transform) {
+        let center = self.cell_center(maze, cell) + Vec3::Y * self.wall_height / 2.0;
+        match maze.topology() {
+            Topology::Square => {
+                let length = self.cell_size + self.wall_thickness;
+                let size = match dir {
+                    Direction::East | Direction::West => {
+                        Vec3::new(self.wall_thickness, self.wall_height, length)
+                    }
+                    _ => Vec3::new(length, self.wall_height, self.wall_thickness),
+                };
+                let (dx, dz) = dir.offset();
+                let offset = Vec3::new(dx as f32, 0.0, dz as f32) * self.cell_size / 2.0;
+                (size, Transform::from_translation(center + offset))
+            }
+            Topology::Hex => {
+                // Lengthen the sides so neighboring walls meet at the
+                // corners, which are 120 degrees.
+                let length = self.hex_radius() + self.wall_thickness / 3f32.sqrt();
+                let size = Vec3::new(length, self.wall_height, self.wall_thickness);
+                let angle = hex_side_angle(dir);
+                let normal = Vec3::new(angle.sin(), 0.0, angle.cos());
+                let transform = Transform::from_translation(center + normal * self.cell_size / 2.0)
+                    .with_rotation(Quat::from_rotation_y(angle));
+                (size, transform)
+            }
+            Topology::Polar => {
+                let ring = maze.coords(cell).1;
+                let (from, to) = self.polar_span(maze, cell);
+                let angle = match dir {
+                    Direction::East if ring > 0 => to,
+                    Direction::West if ring > 0 => from,
+                    _ => panic!("the {dir:?} side of a polar cell is curved"),
+                };
+                let size = Vec3::new(
+                    self.cell_size + self.wall_thickness,
+                    self.wall_height,
+                    self.wall_thickness,
+                );
+                let middle = (ring as f32 + 0.5) * self.cell_size;
+                let translation = self.polar_origin(maze)
+                    + Vec3::Y * self.wall_height / 2.0
+                    + radial(angle) * middle;
+                let transform =
+                    Transform::from_translation(translation).with_rotation(radial_rotation(angle));
+                (size, transform)
+            }
+        }
+    }
+
+    /// The wall on the `dir` side of `cell`, curved or not.
+    pub fn wall_shape(&self, maze: &Maze, cell: usize, dir: Direction) -> WallShape {
+        let ring = maze.coords(cell).1;
+        // The ring whose inner edge the wall follows and the cell spanning
+        // its length.
+        let (edge, span) = match (maze.topology(), dir) {
+            (Topology::Polar, _) if ring == 0 => (
+                1,
+                maze.neighbor(cell, dir)
+                    .expect("the center's sides face the first ring"),
+            ),
+            (Topology::Polar, Direction::North) => (ring, cell),
+            (Topology::Polar, Direction::South | Direction::SouthEast) => {
+                (ring + 1, maze.neighbor(cell, dir).unwrap_or(cell))
+            }
+            _ => {
+                let (size, transform) = self.wall(maze, cell, dir);
+                return WallShape::Box { size, transform };
+            }
+        };
+        let (from, to) = self.polar_span(maze, span);
+        WallShape::Arc {
+            center: self.polar_origin(maze) + Vec3::Y * self.wall_height / 2.0,
+            radius: edge as f32 * self.cell_size,
+            from,
+            to,
+            thickness: self.wall_thickness,
+            height: self.wall_height,
+        }
+    }
+
+    /// Size and placement of the sloped box in `cell` that rises towards
+    /// `rises` to the floor above, see [`floors`](crate::maze::floors). Its
+    /// top runs from the floor at one edge of the cell to the floor above
+    /// at the opposite edge.
+    pub fn ramp(&self, maze: &Maze, cell: usize, rises: Direction) -> (Vec3, Transform) {
+        let (dx, dz) = rises.offset();
+        let slope =
+            Vec3::new(dx as f32, 0.0, dz as f32) * self.cell_size + Vec3::Y * self.floor_spacing();
+        let size = Vec3::new(
+            self.cell_size - self.wall_thickness,
+            self.floor_thickness,
+            slope.length(),
+        );
+        let middle = self.cell_center(maze, cell) + slope.y / 2.0 * Vec3::Y;
+        let mut transform = Transform::from_translation(middle).looking_to(slope, Vec3::Y);
+        transform.translation -= transform.up() * size.y / 2.0;
+        (size, transform)
+    }
+
+    /// The boxes making up the bridge over `cell`, if it is a crossing with
+    /// room for one, see [`weave`](crate::maze::weave): the deck, a ramp up
+    /// to it on either end, railings along them and the walls of the tunnel
+    /// below.
+    pub fn bridge(&self, maze: &Maze, cell: usize) -> Option<Vec<(Vec3, Transform)>> {
+        let [(before, over), (after, back)] = maze.bridge(cell)?;
+        let rise = Vec3::Y * self.floor_spacing();
+        let (dx, dz) = over.offset();
+        let deck = Vec3::new(
+            self.cell_size + self.wall_thickness * dz.abs() as f32,
+            self.floor_thickness,
+            self.cell_size + self.wall_thickness * dx.abs() as f32,
+        );
+        let mut boxes = vec![(
+            deck,
+            Transform::from_translation(
+                self.cell_center(maze, cell) - Vec3::Y * self.floor_thickness / 2.0,
+            ),
+        )];
+        for (ramp, rises) in [(before, over), (after, back)] {
+            boxes.push(self.ramp(maze, ramp, rises));
+            // The tunnel must not lead in under the ramp.
+            let (size, transform) = self.wall(maze, cell, rises.opposite());
+            boxes.push((
+                size,
+                transform.with_translation(transform.translation - rise),
+            ));
+        }
+        for railed in [before, cell, after] {
+            for side in [over.left(), over.right()] {
+                let (size, mut transform) = self.wall(maze, railed, side);
+                if railed != cell {
+                    transform.translation += rise;
+                }
+                boxes.push((size, transform));
+            }
+        }
+        Some(boxes)
+    }
+
+    /// Size and center of the box covering a whole run of walls.
+    pub fn wall_run(&self, run: &WallRun) -> (Vec3, Vec3) {
+        let length = run.len as f32 * self.cell_size + self.wall_thickness;
+        let along = (run.from as f32 + run.len as f32 / 2.0) * self.cell_size;
+        let across = run.line as f32 * self.cell_size;
+        let y = self.floor_y + self.wall_height / 2.0;
+        if run.horizontal {
+            (
+                Vec3::new(length, self.wall_height, self.wall_thickness),
+                Vec3::new(along, y, across),
+            )
+        } else {
+            (
+                Vec3::new(self.wall_thickness, self.wall_height, length),
+                Vec3::new(across, y, along),
+            )
+        }
+    }
+}
+
+/// The shape of one wall, see [`MazeLayout::wall_shape`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WallShape {
+    Box {
+        size: Vec3,
+        transform: Transform,
+    },
+    /// A wall bent along the circle of `radius` around `center`, from angle
+    /// `from` to `to`. Angles are in radians from +X towards +Z, and
+    /// `center` is at half the wall's height.
+    Arc {
+        center: Vec3,
+        radius: f32,
+        from: f32,
+        to: f32,
+        thickness: f32,
+        height: f32,
+    },
+}
+
+impl WallShape {
+    /// A point on the wall.
+    pub fn anchor(&self) -> Vec3 {
+        match *self {
+            WallShape::Box { transform, .. } => transform.translation,
+            WallShape::Arc {
+                center,
+                radius,
+                from,
+                ..
+            } => center + radial(from) * radius,
+        }
+    }
+
+    pub fn translated(mut self, offset: Vec3) -> Self {
+        match &mut self {
+            WallShape::Box { transform, .. } => transform.translation += offset,
+            WallShape::Arc { center, .. } => *center += offset,
+        }
+        self
+    }
+
+    /// The wall as boxes of the given size and placement. Arcs are split
+    /// into straight segments, a few degrees each.
+    pub fn boxes(&self) -> Vec<(Vec3, Transform)> {
+        match *self {
+            WallShape::Box { size, transform } => vec![(size, transform)],
+            WallShape::Arc {
+                center,
+                radius,
+                from,
+                to,
+                thickness,
+                height,
+            } => {
+                let segments = arc_segments(from, to);
+                let half_step = (to - from) / segments as f32 / 2.0;
+                // Chords end on the circle; lengthen them so their outer
+                // corners meet as well.
+                let length = 2.0 * radius * half_step.sin() + thickness * half_step.tan();
+                (0..segments)
+                    .map(|i| {
+                        let angle = from + (2 * i + 1) as f32 * half_step;
+                        let translation = center + radial(angle) * radius * half_step.cos();
+                        (
+                            Vec3::new(thickness, height, length),
+                            Transform::from_translation(translation)
+                                .with_rotation(radial_rotation(angle)),
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Collider parts for [`Collider::compound`], one cuboid per box.
+    pub fn colliders(&self) -> Vec<(Vec3, Quat, Collider)> {
+        self.boxes()
+            .into_iter()
+            .map(|(size, transform)| {
+                (
+                    transform.translation,
+                    transform.rotation,
+                    Collider::cuboid(size.x, size.y, size.z),
+                )
+            })
+            .collect()
+    }
+}
+
+/// How [`spawn_maze`] turns walls into entities.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WallSpawning {
+    /// One entity and static body per wall segment.
+    PerWall,
+    /// Walls merged into straight runs, with one mesh and one compound
+    /// collider per `chunk_size` by `chunk_size` block of cells.
+    Merged { chunk_size: usize },
+}
+
+impl Default for WallSpawning {
+    fn default() -> Self {
+        WallSpawning::Merged { chunk_size: 16 }
+    }
+}
+
+/// The materials of floors and walls, shared by every spawner so that
+/// [`spawn_maze`], streamed chunks and infinite rows all look alike.
+#[derive(Resource, Clone, Debug)]
+pub struct MazeMaterials {
+    pub tile: Handle<StandardMaterial>,
+    pub wall: Handle<StandardMaterial>,
+}
+
+impl FromWorld for MazeMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        MazeMaterials {
+            tile: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                reflectance: 0.3,
+                perceptual_roughness: 0.8,
+                ..default()
+            }),
+            wall: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.8, 0.8, 0.85),
+                perceptual_roughness: 0.6,
+                ..default()
+            }),
+        }
+    }
+}
+
+/// Every top-level entity spawned by [`spawn_maze`], so the world can be
+/// torn down and rebuilt.
+#[derive(Component)]
+pub struct MazeGeometry;
+
+#[derive(Component)]
+pub struct MazeWall;
+
+/// A merged chunk of walls, see [`WallSpawning::Merged`].
+#[derive(Component)]
+pub struct MazeWallChunk(pub (usize, usize));
+
+#[derive(Component)]
+pub struct MazeFloor;
+
+/// A ramp up to the next floor, see [`MazeLayout::ramp`].
+#[derive(Component)]
+pub struct MazeRamp;
+
+/// A bridge over a crossing of a weave maze, see [`MazeLayout::bridge`].
+#[derive(Component)]
+pub struct MazeBridge;
+
+#[derive(Component)]
+pub struct StartMarker;
+
+#[derive(Component)]
+pub struct GoalMarker;
+
+/// Sensor volume over the goal cell; the level is won when the ball is in
+/// its [`CollidingEntities`].
+#[derive(Component)]
+pub struct GoalSensor;
+
+/// Spawns the walls, floor, start/goal markers and goal sensor of
+/// [`CurrentMaze`], replacing whatever was spawned for the previous maze.
+///
+/// Walls are static bodies with matching cuboid colliders, either one per
+/// wall or merged per chunk depending on [`WallSpawning`]. The curved walls
+/// of polar mazes get smooth meshes over colliders made of short straight
+/// segments. The floor collider is made of as few boxes as cover the
+/// tiles, so the ball does not catch on their seams, and is round under
+/// polar mazes. Holes of masked mazes get neither tiles nor collider.
+/// Further floors rest on the walls below them, with holes over the ramps
+/// leading up. Every bridge of a weave maze is one entity with a compound
+/// collider.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_maze(
+    mut commands: Commands,
+    geometry: Query<Entity, With<MazeGeometry>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    maze_materials: Res<MazeMaterials>,
+    maze: Res<CurrentMaze>,
+    layout: Res<MazeLayout>,
+    spawning: Res<WallSpawning>,
+) {
+    for entity in &geometry {
+        commands.entity(entity).despawn_recursive();
+    }
+    let maze = &maze.0;
+    let floor_thickness = layout.floor_thickness;
+
+    // Floor
+    let tile_material = maze_materials.tile.clone();
+    let size = layout.extent(maze);
+    let radius = size.x / 2.0 + layout.wall_thickness / 2.0;
+    let floor_collider = match maze.topology() {
+        Topology::Square => {
+            let boxes = floor_rects(maze)
+                .into_iter()
+                .map(|(xs, ys)| {
+                    let min = Vec2::new(xs.start as f32, ys.start as f32) * layout.cell_size;
+                    let max = Vec2::new(xs.end as f32, ys.end as f32) * layout.cell_size;
+                    let center = (min + max - size) / 2.0;
+                    (
+                        Vec3::new(max.x - min.x, floor_thickness, max.y - min.y),
+                        Vec3::new(center.x, 0.0, center.y),
+                    )
+                })
+                .collect::<Vec<_>>();
+            boxes_collider(&boxes)
+        }
+        Topology::Hex => Collider::cuboid(size.x, floor_thickness, size.y),
+        Topology::Polar => Collider::cylinder(floor_thickness, radius),
+    };
+    commands
+        .spawn((
+            MazeFloor,
+            MazeGeometry,
+            RigidBody::Static,
+            floor_collider,
+            SpatialBundle::from_transform(Transform::from_xyz(
+                size.x / 2.0,
+                layout.floor_y - floor_thickness / 2.0,
+                size.y / 2.0,
+            )),
+        ))
+        .with_children(|floor| match *spawning {
+            _ if maze.topology() == Topology::Polar => {
+                floor.spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cylinder {
+                        radius,
+                        height: floor_thickness,
+                        resolution: 128,
+                        segments: 1,
+                    })),
+                    material: tile_material.clone(),
+                    ..default()
+                });
+            }
+            // Hex tiles do not fit boxes, so one slab covers the whole maze.
+            _ if maze.topology() != Topology::Square => {
+                floor.spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(size.x, floor_thickness, size.y))),
+                    material: tile_material.clone(),
+                    ..default()
+                });
+            }
+            WallSpawning::PerWall => {
+                let tile_mesh = meshes.add(Mesh::from(shape::Box::new(
+                    layout.cell_size,
+                    floor_thickness,
+                    layout.cell_size,
+                )));
+                for cell in maze.cells().filter(|&c| maze.is_enabled(c)) {
+                    let center = layout.cell_center(maze, cell);
+                    floor.spawn(PbrBundle {
+                        mesh: tile_mesh.clone(),
+                        material: tile_material.clone(),
+                        transform: Transform::from_xyz(
+                            center.x - size.x / 2.0,
+                            0.0,
+                            center.z - size.y / 2.0,
+                        ),
+                        ..default()
+                    });
+                }
+            }
+            WallSpawning::Merged { chunk_size } => {
+                // One box per chunk, all in a single mesh. Chunks with holes
+                // fall back to a box per cell.
+                let mut tiles = Vec::new();
+                for y in (0..maze.height()).step_by(chunk_size) {
+                    for x in (0..maze.width()).step_by(chunk_size) {
+                        let (end_x, end_y) = (
+                            (x + chunk_size).min(maze.width()),
+                            (y + chunk_size).min(maze.height()),
+                        );
+                        let cells = (y..end_y)
+                            .flat_map(|y| (x..end_x).map(move |x| (x, y)))
+                            .map(|(x, y)| maze.cell(x, y));
+                        if cells.clone().all(|c| maze.is_enabled(c)) {
+                            let min = Vec2::new(x as f32, y as f32) * layout.cell_size;
+                            let max = Vec2::new(end_x as f32, end_y as f32) * layout.cell_size;
+                            let center = (min + max - size) / 2.0;
+                            tiles.push((
+                                Vec3::new(max.x - min.x, floor_thickness, max.y - min.y),
+                                Vec3::new(center.x, 0.0, center.y),
+                            ));
+                            continue;
+                        }
+                        for cell in cells.filter(|&c| maze.is_enabled(c)) {
+                            let center = layout.cell_center(maze, cell);
+                            tiles.push((
+                                Vec3::new(layout.cell_size, floor_thickness, layout.cell_size),
+                                Vec3::new(center.x - size.x / 2.0, 0.0, center.z - size.y / 2.0),
+                            ));
+                        }
+                    }
+                }
+                floor.spawn(PbrBundle {
+                    mesh: meshes.add(boxes_mesh(&tiles)),
+                    material: tile_material.clone(),
+                    ..default()
+                });
+            }
+        });
+
+    // Upper floors, one slab per run of cells along each row with gaps for
+    // the ramps coming up from below
+    for floor in 1..maze.floors() {
+        let y = layout.floor_y + floor as f32 * layout.floor_spacing() - floor_thickness / 2.0;
+        let mut slabs = Vec::new();
+        for row in 0..maze.height() {
+            let mut from = 0;
+            for x in 0..=maze.width() {
+                let hole =
+                    x == maze.width() || maze.is_open(maze.cell_on(floor, x, row), Direction::Down);
+                if !hole {
+                    continue;
+                }
+                if x > from {
+                    let len = (x - from) as f32 * layout.cell_size;
+                    slabs.push((
+                        Vec3::new(len, floor_thickness, layout.cell_size),
+                        Vec3::new(
+                            from as f32 * layout.cell_size + len / 2.0,
+                            y,
+                            (row as f32 + 0.5) * layout.cell_size,
+                        ),
+                    ));
+                }
+                from = x + 1;
+            }
+        }
+        commands.spawn((
+            MazeFloor,
+            MazeGeometry,
+            RigidBody::Static,
+            boxes_collider(&slabs),
+            PbrBundle {
+                mesh: meshes.add(boxes_mesh(&slabs)),
+                material: tile_material.clone(),
+                ..default()
+            },
+        ));
+    }
+    let ramps = maze
+        .cells()
+        .filter_map(|cell| Some((cell, maze.ramp(cell)?)))
+        .map(|(cell, rises)| layout.ramp(maze, cell, rises));
+    for (size, transform) in ramps {
+        commands.spawn((
+            MazeRamp,
+            MazeGeometry,
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+            PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: tile_material.clone(),
+                transform,
+                ..default()
+            },
+        ));
+    }
+
+    // Walls
+    let wall_material = maze_materials.wall.clone();
+    match *spawning {
+        WallSpawning::PerWall => {
+            let mut wall_meshes = Vec::<(Vec3, Handle<Mesh>)>::new();
+            for (cell, dir) in maze.walls().filter(|(_, dir)| !dir.is_vertical()) {
+                let (size, transform) = match layout.wall_shape(maze, cell, dir) {
+                    WallShape::Box { size, transform } => (size, transform),
+                    arc => {
+                        // Every arc has its own mesh and segmented collider.
+                        let anchor = arc.anchor();
+                        let arc = arc.translated(-anchor);
+                        commands.spawn((
+                            MazeWall,
+                            MazeGeometry,
+                            RigidBody::Static,
+                            Collider::compound(arc.colliders()),
+                            PbrBundle {
+                                mesh: meshes.add(walls_mesh(&[arc])),
+                                material: wall_material.clone(),
+                                transform: Transform::from_translation(anchor),
+                                ..default()
+                            },
+                        ));
+                        continue;
+                    }
+                };
+                let mesh = match wall_meshes.iter().find(|(s, _)| *s == size) {
+                    Some((_, mesh)) => mesh.clone(),
+                    None => {
+                        let mesh = meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z)));
+                        wall_meshes.push((size, mesh.clone()));
+                        mesh
+                    }
+                };
+                commands.spawn((
+                    MazeWall,
+                    MazeGeometry,
+                    RigidBody::Static,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    PbrBundle {
+                        mesh,
+                        material: wall_material.clone(),
+                        transform,
+                        ..default()
+                    },
+                ));
+            }
+        }
+        WallSpawning::Merged { chunk_size } => {
+            let mut chunks = BTreeMap::<_, Vec<_>>::new();
+            let straight = maze.floors() == 1 && maze.tunnel_count() == 0;
+            if maze.topology() == Topology::Square && straight {
+                for run in wall_runs(maze, chunk_size) {
+                    let (size, center) = layout.wall_run(&run);
+                    chunks.entry(run.chunk).or_default().push(WallShape::Box {
+                        size,
+                        transform: Transform::from_translation(center),
+                    });
+                }
+            } else {
+                // Hex and polar sides are too short to be worth merging into
+                // runs, and runs neither follow further floors nor cross
+                // bridges.
+                for (cell, dir) in maze.walls().filter(|(_, dir)| !dir.is_vertical()) {
+                    let (x, y) = maze.coords(cell);
+                    chunks
+                        .entry((x / chunk_size, y / chunk_size))
+                        .or_default()
+                        .push(layout.wall_shape(maze, cell, dir));
+                }
+            }
+            for (chunk, walls) in chunks {
+                // Keep vertices and collider shapes relative to the chunk so
+                // the numbers stay small in huge mazes.
+                let origin = walls[0].anchor();
+                let walls = walls
+                    .into_iter()
+                    .map(|wall| wall.translated(-origin))
+                    .collect::<Vec<_>>();
+                commands.spawn((
+                    MazeWallChunk(chunk),
+                    MazeGeometry,
+                    RigidBody::Static,
+                    Collider::compound(walls.iter().flat_map(WallShape::colliders).collect()),
+                    PbrBundle {
+                        mesh: meshes.add(walls_mesh(&walls)),
+                        material: wall_material.clone(),
+                        transform: Transform::from_translation(origin),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+
+    // Bridges
+    for cell in maze.cells() {
+        let Some(boxes) = layout.bridge(maze, cell) else {
+            continue;
+        };
+        let origin = layout.cell_center(maze, cell);
+        let boxes = boxes
+            .into_iter()
+            .map(|(size, transform)| {
+                (
+                    size,
+                    transform.with_translation(transform.translation - origin),
+                )
+            })
+            .collect::<Vec<_>>();
+        commands.spawn((
+            MazeBridge,
+            MazeGeometry,
+            RigidBody::Static,
+            oriented_boxes_collider(&boxes),
+            PbrBundle {
+                mesh: meshes.add(oriented_boxes_mesh(&boxes)),
+                material: wall_material.clone(),
+                transform: Transform::from_translation(origin),
+                ..default()
+            },
+        ));
+    }
+
+    // Start and goal markers
+    let marker_mesh = meshes.add(Mesh::from(shape::Cylinder {
+        radius: layout.cell_size * 0.3,
+        height: 0.02,
+        resolution: 32,
+        segments: 1,
+    }));
+    commands.spawn((
+        StartMarker,
+        MazeGeometry,
+        PbrBundle {
+            mesh: marker_mesh.clone(),
+            material: materials.add(StandardMaterial {
+                base_color: Color::GREEN,
+                emissive: Color::GREEN * 2.0,
+                ..default()
+            }),
+            transform: Transform::from_translation(
+                layout.cell_center(maze, maze.start()) + Vec3::Y * 0.01,
+            ),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        GoalMarker,
+        MazeGeometry,
+        PbrBundle {
+            mesh: marker_mesh,
+            material: materials.add(StandardMaterial {
+                base_color: Color::GOLD,
+                emissive: Color::GOLD * 4.0,
+                ..default()
+            }),
+            transform: Transform::from_translation(
+                layout.cell_center(maze, maze.goal()) + Vec3::Y * 0.01,
+            ),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        GoalSensor,
+        MazeGeometry,
+        RigidBody::Static,
+        Sensor,
+        Collider::cuboid(
+            layout.cell_size * 0.6,
+            layout.wall_height,
+            layout.cell_size * 0.6,
+        ),
+        CollidingEntities::default(),
+        SpatialBundle::from_transform(Transform::from_translation(
+            layout.cell_center(maze, maze.goal()) + Vec3::Y * layout.wall_height / 2.0,
+        )),
+    ));
+}
+
+/// Rectangles covering the cells of the ground floor of a square maze but
+/// its holes, as ranges of columns and rows: the runs of cells along every
+/// row, each merged with the same run in the rows below it.
+fn floor_rects(maze: &Maze) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut rects = Vec::new();
+    let mut open = Vec::<(Range<usize>, Range<usize>)>::new();
+    for y in 0..maze.height() {
+        let mut next = Vec::new();
+        let mut from = 0;
+        for x in 0..=maze.width() {
+            if x < maze.width() && maze.is_enabled(maze.cell(x, y)) {
+                continue;
+            }
+            if x > from {
+                let run = from..x;
+                next.push(match open.iter().position(|(xs, _)| *xs == run) {
+                    Some(i) => {
+                        let (xs, ys) = open.swap_remove(i);
+                        (xs, ys.start..y + 1)
+                    }
+                    None => (run, y..y + 1),
+                });
+            }
+            from = x + 1;
+        }
+        rects.append(&mut open);
+        open = next;
+    }
+    rects.append(&mut open);
+    rects
+}
+
+/// Rotation about the Y axis that turns +Z towards the `dir` side of a hex
+/// cell.
+fn hex_side_angle(dir: Direction) -> f32 {
+    use std::f32::consts::FRAC_PI_3;
+    match dir {
+        Direction::South => 0.0,
+        Direction::SouthEast => FRAC_PI_3,
+        Direction::NorthEast => 2.0 * FRAC_PI_3,
+        Direction::North => PI,
+        Direction::NorthWest => -2.0 * FRAC_PI_3,
+        Direction::SouthWest => -FRAC_PI_3,
+        Direction::East | Direction::West | Direction::Up | Direction::Down => {
+            unreachable!("hex cells have no {dir:?} side")
+        }
+    }
+}
+
+/// Builds a single mesh out of axis-aligned boxes given as size and center.
+pub fn boxes_mesh(boxes: &[(Vec3, Vec3)]) -> Mesh {
+    let boxes = boxes
+        .iter()
+        .map(|&(size, center)| (size, Transform::from_translation(center)))
+        .collect::<Vec<_>>();
+    oriented_boxes_mesh(&boxes)
+}
+
+/// Builds a single mesh out of boxes given as size and placement.
+pub fn oriented_boxes_mesh(boxes: &[(Vec3, Transform)]) -> Mesh {
+    let mut mesh = MeshBuilder::default();
+    for &(size, transform) in boxes {
+        mesh.add_box(size, transform);
+    }
+    mesh.build()
+}
+
+/// Builds a compound collider out of axis-aligned boxes given as size and
+/// center, the counterpart of [`boxes_mesh`].
+pub fn boxes_collider(boxes: &[(Vec3, Vec3)]) -> Collider {
+    Collider::compound(
+        boxes
+            .iter()
+            .map(|&(size, center)| {
+                (
+                    center,
+                    Quat::IDENTITY,
+                    Collider::cuboid(size.x, size.y, size.z),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Builds a compound collider out of boxes given as size and placement,
+/// the counterpart of [`oriented_boxes_mesh`].
+pub fn oriented_boxes_collider(boxes: &[(Vec3, Transform)]) -> Collider {
+    Collider::compound(
+        boxes
+            .iter()
+            .map(|&(size, transform)| {
+                (
+                    transform.translation,
+                    transform.rotation,
+                    Collider::cuboid(size.x, size.y, size.z),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Builds a single mesh out of walls, with arcs curved smoothly.
+pub fn walls_mesh(walls: &[WallShape]) -> Mesh {
+    let mut mesh = MeshBuilder::default();
+    for wall in walls {
+        match *wall {
+            WallShape::Box { size, transform } => mesh.add_box(size, transform),
+            WallShape::Arc {
+                center,
+                radius,
+                from,
+                to,
+                thickness,
+                height,
+            } => {
+                let segments = arc_segments(from, to);
+                let angles = (0..=segments)
+                    .map(|i| from + (to - from) * i as f32 / segments as f32)
+                    .collect::<Vec<_>>();
+                let up = Vec3::Y * height / 2.0;
+                let point = |angle, offset| center + radial(angle) * (radius + offset);
+                let (inner, outer) = (-thickness / 2.0, thickness / 2.0);
+                for (offset, facing) in [(outer, 1.0), (inner, -1.0)] {
+                    mesh.add_strip(angles.iter().map(|&a| {
+                        let p = point(a, offset);
+                        ([p - up, p + up], radial(a) * facing)
+                    }));
+                }
+                for (side, normal) in [(up, Vec3::Y), (-up, Vec3::NEG_Y)] {
+                    mesh.add_strip(
+                        angles
+                            .iter()
+                            .map(|&a| ([point(a, inner) + side, point(a, outer) + side], normal)),
+                    );
+                }
+                for (angle, facing) in [(from, -1.0), (to, 1.0)] {
+                    let normal = Vec3::new(-angle.sin(), 0.0, angle.cos()) * facing;
+                    mesh.add_strip([inner, outer].map(|offset| {
+                        let p = point(angle, offset);
+                        ([p - up, p + up], normal)
+                    }));
+                }
+            }
+        }
+    }
+    mesh.build()
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn add_box(&mut self, size: Vec3, transform: Transform) {
+        // Normal and the two in-plane axes of every face, ordered so that
+        // `u.cross(v) == normal` and the quads below wind counter-clockwise.
+        const FACES: [(Vec3, Vec3, Vec3); 6] = [
+            (Vec3::X, Vec3::Y, Vec3::Z),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::Z, Vec3::X),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::Y, Vec3::X),
+        ];
+        let half = size / 2.0;
+        for (normal, u, v) in FACES {
+            let base = self.positions.len() as u32;
+            for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                let corner = (normal + u * su + v * sv) * half;
+                self.positions
+                    .push(transform.transform_point(corner).to_array());
+                self.normals.push((transform.rotation * normal).to_array());
+                self.uvs.push([(su + 1.0) / 2.0, (sv + 1.0) / 2.0]);
+            }
+            self.indices
+                .extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+    }
+
+    /// Adds a band of quads between consecutive pairs of points, each pair
+    /// shaded with its normal. The quads face their normals.
+    fn add_strip(&mut self, rows: impl IntoIterator<Item = ([Vec3; 2], Vec3)>) {
+        let base = self.positions.len() as u32;
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        for (i, &(points, normal)) in rows.iter().enumerate() {
+            for (j, point) in points.into_iter().enumerate() {
+                self.positions.push(point.to_array());
+                self.normals.push(normal.to_array());
+                self.uvs
+                    .push([i as f32 / (rows.len() - 1) as f32, j as f32]);
+            }
+        }
+        for (i, pair) in rows.windows(2).enumerate() {
+            let [a, b, c, d] = [0, 1, 3, 2].map(|k| base + 2 * i as u32 + k);
+            let [pa, pb, pc] = [a, b, c].map(|k| Vec3::from(self.positions[k as usize]));
+            if (pb - pa).cross(pc - pa).dot(pair[0].1 + pair[1].1) > 0.0 {
+                self.indices.extend([a, b, c, a, c, d]);
+            } else {
+                self.indices.extend([a, c, b, a, d, c]);
+            }
+        }
+    }
+
+    fn build(self) -> Mesh {
+        Mesh::new(PrimitiveTopology::TriangleList)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+            .with_indices(Some(Indices::U32(self.indices)))
+    }
+}
+
+/// Horizontal unit vector `angle` radians from +X towards +Z.
+fn radial(angle: f32) -> Vec3 {
+    Vec3::new(angle.cos(), 0.0, angle.sin())
+}
+
+/// Rotation about the Y axis that turns +X towards [`radial`]`(angle)`.
+fn radial_rotation(angle: f32) -> Quat {
+    Quat::from_rotation_y(-angle)
+}
+
+/// Number of straight pieces an arc is built from, a few degrees each.
+fn arc_segments(from: f32, to: f32) -> usize {
+    ((to - from) / (PI / 36.0)).ceil().max(1.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{floors::Floors, mask::Mask, weave::Weave};
+
+    #[test]
+    fn cell_centers_map_back_to_their_cells() {
+        let layout = MazeLayout::default();
+        for topology in Topology::ALL {
+            let maze = Maze::with_topology(topology, 7, 5);
+            let extent = layout.extent(&maze);
+            for cell in maze.cells() {
+                let center = layout.cell_center(&maze, cell);
+                assert_eq!(layout.cell_at(&maze, center), Some(cell));
+                assert!(center.x > 0.0 && center.x < extent.x);
+                assert!(center.z > 0.0 && center.z < extent.y);
+            }
+            assert_eq!(layout.cell_at(&maze, Vec3::new(-0.5, 0.0, 1.0)), None);
+            assert_eq!(layout.cell_at(&maze, extent.extend(0.0).xzy() + 0.5), None);
+        }
+    }
+
+    #[test]
+    fn holes_are_not_cells() {
+        let layout = MazeLayout::default();
+        let mask = Mask::from_text("##\n.#\n##").unwrap();
+        for topology in [Topology::Square, Topology::Hex] {
+            let maze = Maze::with_mask(topology, &mask);
+            for cell in maze.cells() {
+                let center = layout.cell_center(&maze, cell);
+                let expected = maze.is_enabled(cell).then_some(cell);
+                assert_eq!(layout.cell_at(&maze, center), expected, "{topology:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn floor_rects_cover_the_cells() {
+        assert_eq!(floor_rects(&Maze::new(4, 3)), [(0..4, 0..3)]);
+
+        let mask = Mask::from_text("###.\n#.##\n#.##").unwrap();
+        let maze = Maze::with_mask(Topology::Square, &mask);
+        let rects = floor_rects(&maze);
+        for cell in maze.cells() {
+            let (x, y) = maze.coords(cell);
+            let covering = rects
+                .iter()
+                .filter(|(xs, ys)| xs.contains(&x) && ys.contains(&y))
+                .count();
+            assert_eq!(covering, usize::from(maze.is_enabled(cell)), "{x}, {y}");
+        }
+        // The two rows alike share their rectangles.
+        assert_eq!(rects.len(), 3);
+    }
+
+    #[test]
+    fn walls_sit_between_neighbors() {
+        let layout = MazeLayout::default();
+        for topology in Topology::ALL {
+            let maze = Maze::with_topology(topology, 4, 4);
+            for cell in [maze.cell(1, 1), 0] {
+                for (dir, other) in maze.neighbors(cell) {
+                    let middle = match layout.wall_shape(&maze, cell, dir) {
+                        WallShape::Box { size, transform } => {
+                            // The wall runs across the line between the two
+                            // centers.
+                            let length = if size.x > size.z {
+                                Vec3::X * size.x
+                            } else {
+                                Vec3::Z * size.z
+                            };
+                            let along = transform.rotation * length;
+                            let across =
+                                layout.cell_center(&maze, other) - layout.cell_center(&maze, cell);
+                            assert!(along.dot(across).abs() < 1e-3, "{topology:?} {dir:?}");
+                            transform.translation
+                        }
+                        WallShape::Arc {
+                            center,
+                            radius,
+                            from,
+                            to,
+                            ..
+                        } => center + radial((from + to) / 2.0) * radius,
+                    };
+                    // Just off the wall on either side are the two cells.
+                    for c in [cell, other] {
+                        let toward = (layout.cell_center(&maze, c) - middle).xz().normalize();
+                        let probe = middle + toward.extend(0.0).xzy() * 0.1;
+                        assert_eq!(
+                            layout.cell_at(&maze, probe),
+                            Some(c),
+                            "{topology:?} {dir:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ramps_join_the_floors() {
+        let layout = MazeLayout::default();
+        let maze = Floors::default().generate(&generate::Prim, 5, 4, 3);
+        for cell in maze.cells() {
+            let ball = layout.cell_center(&maze, cell) + Vec3::Y * 0.45;
+            assert_eq!(layout.cell_at(&maze, ball), Some(cell));
+        }
+
+        let ramps = maze
+            .cells()
+            .filter_map(|cell| Some((cell, maze.ramp(cell)?)))
+            .collect::<Vec<_>>();
+        assert_eq!(ramps.len(), 2);
+        for (cell, rises) in ramps {
+            let (size, transform) = layout.ramp(&maze, cell, rises);
+            let (dx, dz) = rises.offset();
+            let edge = Vec3::new(dx as f32, 0.0, dz as f32) * layout.cell_size / 2.0;
+            // The top face starts at the edge it is entered from and ends at
+            // the opposite edge of the cell above.
+            let top =
+                |end: f32| transform.transform_point(Vec3::new(0.0, size.y, end * size.z) / 2.0);
+            let above = maze.neighbor(cell, Direction::Up).unwrap();
+            assert!(top(1.0).distance(layout.cell_center(&maze, cell) - edge) < 1e-4);
+            assert!(top(-1.0).distance(layout.cell_center(&maze, above) + edge) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn bridges_span_their_crossings() {
+        let layout = MazeLayout::default();
+        let maze = Weave::default().generate(9, 9, 4);
+        let crossing = maze.cells().find(|&c| maze.bridge(c).is_some()).unwrap();
+        let tunnel = maze.tunnel_under(crossing).unwrap();
+        let deck = layout.cell_center(&maze, crossing);
+        assert_eq!(deck.y, layout.floor_y + layout.floor_spacing());
+        assert_eq!(layout.cell_at(&maze, deck + Vec3::Y * 0.45), Some(crossing));
+        let below = layout.cell_center(&maze, tunnel) + Vec3::Y * 0.45;
+        assert_eq!(layout.cell_at(&maze, below), Some(tunnel));
+
+        let boxes = layout.bridge(&maze, crossing).unwrap();
+        let (size, transform) = boxes[0];
+        assert_eq!(transform.translation.y + size.y / 2.0, deck.y);
+        // Both ramps end at the deck, right above the walls that close the
+        // tunnel off from the space under them.
+        for (ramp, wall) in [(boxes[1], boxes[2]), (boxes[3], boxes[4])] {
+            let (size, transform) = ramp;
+            let top = transform.transform_point(Vec3::new(0.0, size.y, -size.z) / 2.0);
+            assert!((top.y - deck.y).abs() < 1e-4);
+            assert!(top.xz().distance(wall.1.translation.xz()) < 1e-4);
+            assert!(wall.1.translation.y + wall.0.y / 2.0 < deck.y - size.y);
+        }
+        assert!(layout.bridge(&maze, tunnel).is_none());
+    }
+
+    #[test]
+    fn arcs_follow_their_circle() {
+        let layout = MazeLayout::default();
+        let maze = Maze::with_topology(Topology::Polar, 6, 3);
+        let arc = layout.wall_shape(&maze, maze.cell(4, 1), Direction::North);
+        let WallShape::Arc { center, radius, .. } = arc else {
+            panic!("inner walls of a ring are curved");
+        };
+        assert_eq!(radius, layout.cell_size);
+
+        let boxes = arc.boxes();
+        // A sixth of the circle, in pieces of at most five degrees.
+        assert_eq!(boxes.len(), 12);
+        for (size, transform) in boxes {
+            let offset = transform.translation - center;
+            assert!((offset.length() - radius).abs() < 0.01 * radius);
+            // Every piece lies along the circle.
+            assert!((transform.rotation * Vec3::Z).dot(offset).abs() < 1e-4);
+            assert!(size.z < radius * 0.1);
+        }
+
+        let mesh = walls_mesh(&[arc]);
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("mesh has positions");
+        };
+        for position in positions {
+            let distance = (Vec3::from(*position) - center).xz().length();
+            assert!((distance - radius).abs() <= layout.wall_thickness / 2.0 + 1e-4);
+        }
+    }
+}